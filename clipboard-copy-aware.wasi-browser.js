@@ -57,4 +57,21 @@ const {
 })
 export default __napiModule.exports
 export const clipboardInitialize = __napiModule.exports.clipboardInitialize
-export const FileType = __napiModule.exports.FileType
+export const Selection = __napiModule.exports.Selection
+export const clipboardPushSnapshot = __napiModule.exports.clipboardPushSnapshot
+export const clipboardOnEvent = __napiModule.exports.clipboardOnEvent
+export const clipboardShutdown = __napiModule.exports.clipboardShutdown
+export const updateConfig = __napiModule.exports.updateConfig
+export const ClipboardWatcher = __napiModule.exports.ClipboardWatcher
+export const watchClipboard = __napiModule.exports.watchClipboard
+export const ClipboardEventKind = __napiModule.exports.ClipboardEventKind
+export const Subscription = __napiModule.exports.Subscription
+export const subscribe = __napiModule.exports.subscribe
+export const clipboardOnError = __napiModule.exports.clipboardOnError
+export const getStatus = __napiModule.exports.getStatus
+export const getPrometheusMetrics = __napiModule.exports.getPrometheusMetrics
+export const WalkLimit = __napiModule.exports.WalkLimit
+export const ArchiveFormat = __napiModule.exports.ArchiveFormat
+export const ArchiveLimit = __napiModule.exports.ArchiveLimit
+export const HashAlgorithm = __napiModule.exports.HashAlgorithm
+export const CodeLanguage = __napiModule.exports.CodeLanguage
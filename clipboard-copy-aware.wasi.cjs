@@ -109,4 +109,21 @@ const { instance: __napiInstance, module: __wasiModule, napiModule: __napiModule
 })
 module.exports = __napiModule.exports
 module.exports.clipboardInitialize = __napiModule.exports.clipboardInitialize
-module.exports.FileType = __napiModule.exports.FileType
+module.exports.Selection = __napiModule.exports.Selection
+module.exports.clipboardPushSnapshot = __napiModule.exports.clipboardPushSnapshot
+module.exports.clipboardOnEvent = __napiModule.exports.clipboardOnEvent
+module.exports.clipboardShutdown = __napiModule.exports.clipboardShutdown
+module.exports.updateConfig = __napiModule.exports.updateConfig
+module.exports.ClipboardWatcher = __napiModule.exports.ClipboardWatcher
+module.exports.watchClipboard = __napiModule.exports.watchClipboard
+module.exports.ClipboardEventKind = __napiModule.exports.ClipboardEventKind
+module.exports.Subscription = __napiModule.exports.Subscription
+module.exports.subscribe = __napiModule.exports.subscribe
+module.exports.clipboardOnError = __napiModule.exports.clipboardOnError
+module.exports.getStatus = __napiModule.exports.getStatus
+module.exports.getPrometheusMetrics = __napiModule.exports.getPrometheusMetrics
+module.exports.WalkLimit = __napiModule.exports.WalkLimit
+module.exports.ArchiveFormat = __napiModule.exports.ArchiveFormat
+module.exports.ArchiveLimit = __napiModule.exports.ArchiveLimit
+module.exports.HashAlgorithm = __napiModule.exports.HashAlgorithm
+module.exports.CodeLanguage = __napiModule.exports.CodeLanguage
@@ -0,0 +1,597 @@
+#![deny(clippy::all)]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::Ordering;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+
+use clipboard_copy_aware_core::{
+    analysis::{
+        archive::file_archive_format,
+        backend::ClipboardBackend,
+        check_dropped_files, classify_file,
+        metadata::{file_metadata, hash_event, hash_files},
+        pattern::DEFAULT_CASE_SENSITIVE,
+        record::{Recorder, RecordingBackend, ReplayBackend},
+    },
+    global::{
+        path_rules, set_file_rules, set_log_sink, set_path_rules, DETECT_CODE, HASH_BLAKE3, HASH_SHA256, MAX_ARCHIVE_BYTES,
+        MAX_ARCHIVE_DEPTH, MAX_ARCHIVE_ENTRIES, MAX_HASH_BYTES, MAX_TABLE_CELLS, MAX_WALK_DEPTH, MAX_WALK_FILES,
+        REPORT_UNMATCHED, TEXT_PREVIEW_CHARS, WALK_TIMEOUT_MS,
+    },
+    analyze_clipboard, open_backend, run_monitor, setup_clipboard_monitor, ArchiveInfo, ClipboardEvent, ClipboardReport, CodeInfo,
+    DirectoryInfo, EventFilter, EventKind, FileCategory, FileHash, FileInfo, FileRules, FilesInfo, FilesSummary, MonitorError, PathPattern,
+    PathRules, Selection, ShotInfo, TableInfo, TextInfo,
+};
+
+// 命令行工具：不写 JS 也能在工作站上观察剪贴板事件，所有输出均为 NDJSON (每行一个 JSON)
+#[derive(Parser)]
+#[command(name = "clipboard-copy-aware", version, about = "Inspect and watch clipboard copy events")]
+struct Cli {
+    #[command(flatten)]
+    rules: RuleArgs,
+
+    #[command(flatten)]
+    filter: FilterArgs,
+
+    /// 表格事件中最多输出的单元格数量
+    #[arg(long, global = true)]
+    max_table_cells: Option<usize>,
+
+    /// 文本事件预览的最大字符数，0 表示不带预览
+    #[arg(long, global = true)]
+    text_preview: Option<usize>,
+
+    /// 遍历复制的文件夹的最大深度，0 表示不遍历文件夹
+    #[arg(long, global = true)]
+    max_walk_depth: Option<usize>,
+
+    /// 一次复制中最多遍历的文件数
+    #[arg(long, global = true)]
+    max_walk_files: Option<usize>,
+
+    /// 一次复制中遍历文件夹的最长时间 (毫秒)
+    #[arg(long, global = true)]
+    walk_timeout_ms: Option<u64>,
+
+    /// 列出复制的压缩包时的最大嵌套深度，0 表示不列出压缩包
+    #[arg(long, global = true)]
+    max_archive_depth: Option<usize>,
+
+    /// 一次复制中最多列出的压缩包条目数
+    #[arg(long, global = true)]
+    max_archive_entries: Option<usize>,
+
+    /// 一次复制中最多解压的字节数
+    #[arg(long, global = true)]
+    max_archive_bytes: Option<u64>,
+
+    /// 为复制的文件计算的哈希，可重复指定
+    #[arg(long = "hash", value_enum, global = true)]
+    hashes: Vec<HashArg>,
+
+    /// 超过这个大小 (字节) 的文件不计算哈希
+    #[arg(long, global = true)]
+    max_hash_bytes: Option<u64>,
+
+    /// 没有匹配任何分类的文件也输出，分类为 OTHER
+    #[arg(long, global = true)]
+    report_unmatched: bool,
+
+    /// 不识别代码片段，复制的代码按普通文本输出
+    #[arg(long, global = true)]
+    no_detect_code: bool,
+
+    /// 把内部日志输出到 stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 持续监听，每次事件输出一行 JSON
+    Watch {
+        /// 要监听的选区，可重复指定
+        #[arg(long = "selection", value_enum, default_value = "clipboard")]
+        selections: Vec<SelectionArg>,
+    },
+    /// 输出当前剪贴板上的全部格式
+    Inspect {
+        /// 每个格式预览的最大字节数
+        #[arg(long, default_value_t = 64)]
+        preview: usize,
+    },
+    /// 用分类规则和文件头判断路径属于哪个分类
+    Classify {
+        paths: Vec<String>,
+    },
+    /// 监听的同时把每次分析的剪贴板内容录制到文件 (只保存分析读取的格式数据)
+    Record {
+        output: PathBuf,
+        /// 要监听的选区，可重复指定
+        #[arg(long = "selection", value_enum, default_value = "clipboard")]
+        selections: Vec<SelectionArg>,
+    },
+    /// 把录制文件重新跑一遍分析，输出与 watch 相同的事件
+    Replay {
+        input: PathBuf,
+        /// 按录制时的时间间隔回放 (包含节流)，默认逐条立即分析
+        #[arg(long)]
+        realtime: bool,
+    },
+}
+
+// 覆盖默认的文件分类，与 clipboardInitialize 的 categories 对应
+#[derive(Args)]
+struct RuleArgs {
+    /// 自定义文件分类，格式为 NAME[:PRIORITY]=ITEM,ITEM...，含 "/" 的项是 MIME 类型，其余是后缀；
+    /// 可重复指定，指定后替换全部默认分类 (IMAGE / EXCEL / CODE)
+    #[arg(long = "file-category", global = true, value_name = "SPEC", value_parser = parse_category)]
+    file_categories: Vec<FileCategory>,
+    /// 只上报这些目录下的文件 (glob，如 "~/Documents/Contracts/**")，可重复指定
+    #[arg(long, global = true, value_name = "GLOB")]
+    include: Vec<String>,
+    /// 不上报这些目录下的文件 (glob，如 "**/node_modules")，可重复指定
+    #[arg(long, global = true, value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// 与 --include 相同，但使用正则在整个路径中查找
+    #[arg(long, global = true, value_name = "REGEX")]
+    include_regex: Vec<String>,
+    /// 与 --exclude 相同，但使用正则在整个路径中查找
+    #[arg(long, global = true, value_name = "REGEX")]
+    exclude_regex: Vec<String>,
+    /// 路径规则是否区分大小写，默认与系统一致 (Windows / macOS 不区分)
+    #[arg(long, global = true, value_enum)]
+    path_case: Option<PathCaseArg>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PathCaseArg {
+    Sensitive,
+    Insensitive,
+}
+
+// 解析 --file-category，如 "DOCS:10=.pdf,.docx,application/pdf"
+fn parse_category(spec: &str) -> Result<FileCategory, String> {
+    let (head, items) = spec.split_once('=').ok_or("expected NAME[:PRIORITY]=ITEM,ITEM...")?;
+    let (name, priority) = match head.split_once(':') {
+        Some((name, priority)) => {
+            (name, priority.trim().parse().map_err(|_| format!("invalid priority {:?}", priority))?)
+        }
+        None => (head, 0),
+    };
+    let (mime_types, extensions): (Vec<String>, Vec<String>) = items
+        .split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(str::to_string)
+        .partition(|item| item.contains('/'));
+    FileCategory::new(name, &extensions, &mime_types, priority).map_err(|e| e.message)
+}
+
+// 只输出匹配的事件 (watch / record / replay)，与 subscribe 的 filter 对应
+#[derive(Args)]
+struct FilterArgs {
+    /// 事件类型，可重复指定
+    #[arg(long = "kind", value_enum, global = true)]
+    kinds: Vec<KindArg>,
+    /// 文件分类名称，只保留这些分类的文件，可重复指定
+    #[arg(long = "category", global = true)]
+    categories: Vec<String>,
+    /// 来源进程名 (不区分大小写)，可重复指定
+    #[arg(long = "pname", global = true)]
+    pnames: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KindArg {
+    Files,
+    Image,
+    Table,
+    Text,
+    Code,
+    Cleared,
+    Hashes,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HashArg {
+    Sha256,
+    Blake3,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SelectionArg {
+    Clipboard,
+    Primary,
+    Secondary,
+}
+
+impl From<SelectionArg> for Selection {
+    fn from(value: SelectionArg) -> Self {
+        match value {
+            SelectionArg::Clipboard => Selection::CLIPBOARD,
+            SelectionArg::Primary => Selection::PRIMARY,
+            SelectionArg::Secondary => Selection::SECONDARY,
+        }
+    }
+}
+
+impl From<KindArg> for EventKind {
+    fn from(value: KindArg) -> Self {
+        match value {
+            KindArg::Files => EventKind::FILES,
+            KindArg::Image => EventKind::IMAGE,
+            KindArg::Table => EventKind::TABLE,
+            KindArg::Text => EventKind::TEXT,
+            KindArg::Code => EventKind::CODE,
+            KindArg::Cleared => EventKind::CLEARED,
+            KindArg::Hashes => EventKind::HASHES,
+        }
+    }
+}
+
+impl From<FilterArgs> for EventFilter {
+    fn from(value: FilterArgs) -> Self {
+        Self {
+            kinds: value.kinds.into_iter().map(Into::into).collect(),
+            categories: value.categories,
+            pnames: value.pnames,
+        }
+    }
+}
+
+fn apply_rules(rules: RuleArgs) -> Result<(), MonitorError> {
+    if !rules.file_categories.is_empty() {
+        set_file_rules(FileRules::new(rules.file_categories)?);
+    }
+
+    let case_sensitive = match rules.path_case {
+        Some(PathCaseArg::Sensitive) => true,
+        Some(PathCaseArg::Insensitive) => false,
+        None => DEFAULT_CASE_SENSITIVE,
+    };
+    let globs = |patterns: &[String]| -> Result<Vec<PathPattern>, MonitorError> {
+        patterns.iter().map(|p| PathPattern::glob(p, case_sensitive)).collect()
+    };
+    let regexes = |patterns: &[String]| -> Result<Vec<PathPattern>, MonitorError> {
+        patterns.iter().map(|p| PathPattern::regex(p, case_sensitive)).collect()
+    };
+    let mut include = globs(&rules.include)?;
+    include.extend(regexes(&rules.include_regex)?);
+    let mut exclude = globs(&rules.exclude)?;
+    exclude.extend(regexes(&rules.exclude_regex)?);
+    set_path_rules(PathRules { include, exclude });
+    Ok(())
+}
+
+fn emit(value: Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", value);
+    let _ = stdout.flush();
+}
+
+fn file_json(file: &FileInfo) -> Value {
+    json!({
+        "name": file.name,
+        "path": file.path,
+        "category": file.category,
+        "extension": file.extension,
+        "mimeType": file.mime_type,
+        "extensionMismatch": file.extension_mismatch,
+        "directory": file.directory,
+        "archiveEntry": file.archive_entry,
+        "size": file.metadata.size,
+        "modifiedAt": file.metadata.modified_at,
+        "createdAt": file.metadata.created_at,
+        "uid": file.metadata.uid,
+        "permissions": file.metadata.permissions,
+        "readonly": file.metadata.readonly,
+        "pname": file.pname,
+        "pid": file.pid,
+        "selection": format!("{:?}", file.selection),
+    })
+}
+
+fn directory_json(directory: &DirectoryInfo) -> Value {
+    json!({
+        "path": directory.path,
+        "files": directory.files,
+        "categories": directory.categories,
+        "limit": directory.limit.map(|limit| format!("{:?}", limit)),
+    })
+}
+
+fn archive_json(archive: &ArchiveInfo) -> Value {
+    json!({
+        "path": archive.path,
+        "format": format!("{:?}", archive.format),
+        "entries": archive.entries,
+        "categories": archive.categories,
+        "limit": archive.limit.map(|limit| format!("{:?}", limit)),
+        "error": archive.error,
+    })
+}
+
+fn summary_json(summary: &FilesSummary) -> Value {
+    let categories: serde_json::Map<String, Value> = summary
+        .categories
+        .iter()
+        .map(|(category, c)| (category.clone(), json!({ "files": c.files, "bytes": c.bytes })))
+        .collect();
+    json!({ "files": summary.files, "bytes": summary.bytes, "categories": categories })
+}
+
+fn files_json(files: &FilesInfo) -> Value {
+    json!({
+        "files": files.files.iter().map(file_json).collect::<Vec<_>>(),
+        "directories": files.directories.iter().map(directory_json).collect::<Vec<_>>(),
+        "archives": files.archives.iter().map(archive_json).collect::<Vec<_>>(),
+        "summary": summary_json(&files.summary()),
+        "truncated": files.truncated(),
+    })
+}
+
+fn shot_json(shot: &ShotInfo) -> Value {
+    json!({
+        "pname": shot.pname,
+        "pid": shot.pid,
+        "size": shot.data.len(),
+        "selection": format!("{:?}", shot.selection),
+    })
+}
+
+fn table_json(table: &TableInfo) -> Value {
+    json!({
+        "pname": table.pname,
+        "pid": table.pid,
+        "format": table.format,
+        "selection": format!("{:?}", table.selection),
+        "rows": table.rows,
+        "columns": table.columns,
+        "cells": table.cells,
+        "truncated": table.truncated,
+    })
+}
+
+fn text_json(text: &TextInfo) -> Value {
+    json!({
+        "pname": text.pname,
+        "pid": text.pid,
+        "format": text.format,
+        "selection": format!("{:?}", text.selection),
+        "chars": text.chars,
+        "bytes": text.bytes,
+        "lines": text.lines,
+        "preview": text.preview,
+        "hash": text.hash,
+    })
+}
+
+fn code_json(code: &CodeInfo) -> Value {
+    json!({
+        "text": text_json(&code.text),
+        "language": format!("{:?}", code.language),
+        "confidence": code.confidence,
+    })
+}
+
+fn hashes_json(hashes: &[FileHash]) -> Value {
+    hashes
+        .iter()
+        .map(|hash| {
+            json!({
+                "path": hash.path,
+                "category": hash.category,
+                "sha256": hash.sha256,
+                "blake3": hash.blake3,
+            })
+        })
+        .collect()
+}
+
+fn emit_event(event: ClipboardEvent) {
+    let (kind, payload) = match &event.report {
+        ClipboardReport::Files(files) => ("files", files_json(files)),
+        ClipboardReport::Shot(shot) => ("image", json!({ "image": shot_json(shot) })),
+        ClipboardReport::Table(table) => ("table", json!({ "table": table_json(table) })),
+        ClipboardReport::Text(text) => ("text", json!({ "text": text_json(text) })),
+        ClipboardReport::Code(code) => ("code", json!({ "code": code_json(code) })),
+        ClipboardReport::Cleared => ("cleared", json!({})),
+        ClipboardReport::Hashes(hashes) => {
+            ("hashes", json!({ "eventSeq": hashes.event_seq, "files": hashes_json(&hashes.files) }))
+        }
+    };
+    let mut line = json!({
+        "event": kind,
+        "seq": event.seq,
+        "timestamp": event.timestamp,
+        "pid": event.pid,
+        "pname": event.pname,
+        "selection": format!("{:?}", event.selection),
+        "formats": event.formats,
+    });
+    if let (Some(line), Value::Object(payload)) = (line.as_object_mut(), payload) {
+        line.extend(payload);
+    }
+    emit(line);
+}
+
+// 按过滤条件输出事件
+fn event_sink(filter: &EventFilter) -> impl FnMut(ClipboardEvent) + Send + '_ {
+    move |event| {
+        if let Some(event) = filter.apply(&event) {
+            emit_event(event);
+        }
+    }
+}
+
+fn fail(e: impl std::fmt::Display) -> ExitCode {
+    eprintln!("clipboard-copy-aware: {}", e);
+    ExitCode::FAILURE
+}
+
+fn watch(selections: Vec<Selection>, filter: &EventFilter) -> ExitCode {
+    match setup_clipboard_monitor(&selections, || {}, event_sink(filter)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => fail(e),
+    }
+}
+
+fn record(output: PathBuf, selections: Vec<Selection>, filter: &EventFilter) -> ExitCode {
+    let recorder = match Recorder::create(&output) {
+        Ok(recorder) => recorder,
+        Err(e) => return fail(format!("{}: {}", output.display(), e)),
+    };
+    let backend = match open_backend(&selections) {
+        Ok(backend) => backend,
+        Err(e) => return fail(e),
+    };
+    match run_monitor(&mut RecordingBackend::new(backend, recorder), event_sink(filter)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => fail(e),
+    }
+}
+
+fn replay(input: PathBuf, realtime: bool, filter: &EventFilter) -> ExitCode {
+    let mut backend = match ReplayBackend::open(&input) {
+        Ok(backend) => backend.realtime(realtime),
+        Err(e) => return fail(format!("{}: {}", input.display(), e)),
+    };
+    let mut sink = event_sink(filter);
+    if realtime {
+        // 回放后端不会出错
+        let _ = run_monitor(&mut backend, sink);
+    } else {
+        // 逐条分析，不经过节流，保证每条记录的结果都可复现
+        while backend.wait_for_change() {
+            if let Some(event) = analyze_clipboard(&mut backend) {
+                let hashes = hash_event(&event);
+                sink(event);
+                if let Some(hashes) = hashes {
+                    sink(hashes);
+                }
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn inspect(preview: usize) -> ExitCode {
+    let mut backend = match open_backend(&[Selection::CLIPBOARD]) {
+        Ok(backend) => backend,
+        Err(e) => return fail(e),
+    };
+
+    let owner = backend.owner();
+    emit(json!({
+        "event": "owner",
+        "pid": owner.as_ref().map(|(pid, _)| *pid),
+        "pname": owner.as_ref().map(|(_, pname)| pname.clone()),
+    }));
+    let Some(formats) = backend.formats() else {
+        return fail("clipboard is unavailable");
+    };
+    for format in formats {
+        let data = backend.read(&format);
+        emit(json!({
+            "event": "format",
+            "format": format,
+            "size": data.as_ref().map(Vec::len),
+            "preview": data.as_ref().map(|d| String::from_utf8_lossy(&d[..d.len().min(preview)]).into_owned()),
+        }));
+    }
+    ExitCode::SUCCESS
+}
+
+fn classify(paths: Vec<String>) -> ExitCode {
+    for path in paths {
+        // 文件夹按复制时的规则遍历，输出匹配的文件和统计
+        if Path::new(&path).is_dir() {
+            let report = check_dropped_files(std::slice::from_ref(&path), 0, "", Selection::CLIPBOARD);
+            let hashes = hashes_json(&hash_files(&report.files));
+            emit(json!({ "path": path, "report": files_json(&report), "hashes": hashes }));
+            continue;
+        }
+        let class = classify_file(&path);
+        // 压缩包按复制时的规则列出其中的文件
+        let report = class.content
+            .and_then(|content| file_archive_format(Path::new(&path), content))
+            .map(|_| files_json(&check_dropped_files(std::slice::from_ref(&path), 0, "", Selection::CLIPBOARD)));
+        let metadata = file_metadata(Path::new(&path));
+        emit(json!({
+            "path": path,
+            // 被路径规则拦下的原因，watch 时不会上报这个文件
+            "skipped": path_rules().reject_reason(&path),
+            "extension": class.extension,
+            "mimeType": class.content.map(|c| c.mime),
+            "extensionMismatch": class.mismatch(),
+            "category": class.category,
+            "size": metadata.size,
+            "modifiedAt": metadata.modified_at,
+            "report": report,
+        }));
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // stdout 只输出 NDJSON，日志按需转到 stderr
+    let verbose = cli.verbose;
+    let _ = set_log_sink(Box::new(move |msg| {
+        if verbose {
+            eprintln!("{}", msg);
+        }
+    }));
+    if let Err(e) = apply_rules(cli.rules) {
+        return fail(e);
+    }
+    if let Some(max_cells) = cli.max_table_cells {
+        MAX_TABLE_CELLS.store(max_cells, Ordering::Relaxed);
+    }
+    if let Some(preview_chars) = cli.text_preview {
+        TEXT_PREVIEW_CHARS.store(preview_chars, Ordering::Relaxed);
+    }
+    if let Some(depth) = cli.max_walk_depth {
+        MAX_WALK_DEPTH.store(depth, Ordering::Relaxed);
+    }
+    if let Some(files) = cli.max_walk_files {
+        MAX_WALK_FILES.store(files, Ordering::Relaxed);
+    }
+    if let Some(timeout) = cli.walk_timeout_ms {
+        WALK_TIMEOUT_MS.store(timeout, Ordering::Relaxed);
+    }
+    if let Some(depth) = cli.max_archive_depth {
+        MAX_ARCHIVE_DEPTH.store(depth, Ordering::Relaxed);
+    }
+    if let Some(entries) = cli.max_archive_entries {
+        MAX_ARCHIVE_ENTRIES.store(entries, Ordering::Relaxed);
+    }
+    if let Some(bytes) = cli.max_archive_bytes {
+        MAX_ARCHIVE_BYTES.store(bytes, Ordering::Relaxed);
+    }
+    HASH_SHA256.store(cli.hashes.contains(&HashArg::Sha256), Ordering::Relaxed);
+    HASH_BLAKE3.store(cli.hashes.contains(&HashArg::Blake3), Ordering::Relaxed);
+    if let Some(bytes) = cli.max_hash_bytes {
+        MAX_HASH_BYTES.store(bytes, Ordering::Relaxed);
+    }
+    REPORT_UNMATCHED.store(cli.report_unmatched, Ordering::Relaxed);
+    DETECT_CODE.store(!cli.no_detect_code, Ordering::Relaxed);
+
+    let filter = EventFilter::from(cli.filter);
+    match cli.command {
+        Command::Watch { selections } => watch(selections.into_iter().map(Into::into).collect(), &filter),
+        Command::Inspect { preview } => inspect(preview),
+        Command::Classify { paths } => classify(paths),
+        Command::Record { output, selections } => {
+            record(output, selections.into_iter().map(Into::into).collect(), &filter)
+        }
+        Command::Replay { input, realtime } => replay(input, realtime, &filter),
+    }
+}
@@ -0,0 +1,700 @@
+pub mod archive;
+pub mod backend;
+pub mod code;
+pub mod filter;
+pub mod host;
+pub mod metadata;
+pub mod mock;
+pub mod pattern;
+pub mod record;
+pub mod sniff;
+pub mod table;
+pub mod text;
+pub mod walk;
+#[cfg(windows)]
+pub mod win32;
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub mod x11;
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub mod wayland;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::{
+    global::{
+        file_rules, path_rules, ArchiveInfo, CodeInfo, DirectoryInfo, FileInfo, FileMetadata, FileRules, FilesInfo, HashesInfo, Selection, ShotInfo, TableInfo, TextInfo, EVENT_SEQ, LAST_IMG_SIZE,
+        DETECT_CODE, MAX_TABLE_CELLS, OTHER_CATEGORY, REPORT_UNMATCHED, TEXT_PREVIEW_CHARS,
+    },
+    report_error_log, report_info_log,
+    stats::IMAGES_DEDUPLICATED,
+    utils::now_millis,
+};
+use self::metadata::file_metadata;
+use self::code::detect_language;
+use self::archive::{file_archive_format, list_archive, ArchiveBudget, ArchiveFormat};
+use self::pattern::PathRules;
+use self::sniff::{sniff_file, ContentType};
+use self::table::parse_table;
+use self::text::{decode_text, text_info};
+use self::walk::{walk_directory, WalkBudget, WalkLimit};
+use self::backend::{
+    parse_drop_files, parse_uri_list, ClipboardBackend, FORMAT_BITMAP, FORMAT_BMP, FORMAT_CSV, FORMAT_CSV_MIME,
+    FORMAT_DIB, FORMAT_GNOME_FILES, FORMAT_HDROP, FORMAT_HTML, FORMAT_HTML_MIME, FORMAT_JPEG, FORMAT_PLAIN_MIME,
+    FORMAT_PNG, FORMAT_TEXT_MIME, FORMAT_UNICODETEXT, FORMAT_URI_LIST, FORMAT_UTF8_STRING,
+};
+
+// 按优先级排列的图片格式，取第一个能读到数据的
+const IMAGE_FORMATS: [&str; 4] = [FORMAT_DIB, FORMAT_PNG, FORMAT_BMP, FORMAT_JPEG];
+// 表格数据格式 (Windows 注册格式 / MIME)，CSV 的结构更可靠，优先解析
+const TABLE_FORMATS: [&str; 4] = [FORMAT_CSV, FORMAT_CSV_MIME, FORMAT_HTML, FORMAT_HTML_MIME];
+// 纯文本格式，优先取 UTF-8 / UTF-16 编码明确的格式
+const TEXT_FORMATS: [&str; 4] = [FORMAT_UNICODETEXT, FORMAT_TEXT_MIME, FORMAT_UTF8_STRING, FORMAT_PLAIN_MIME];
+
+// 一次剪贴板变化的分析结果
+#[derive(Debug, Clone)]
+pub enum ClipboardReport {
+    Files(FilesInfo),
+    Shot(ShotInfo),
+    Table(TableInfo),
+    Text(TextInfo),
+    // 识别为源代码的文本
+    Code(CodeInfo),
+    // 之前某个文件事件中文件的哈希
+    Hashes(HashesInfo),
+    // 剪贴板被清空
+    Cleared,
+}
+
+// 上报给调用方的事件：分析结果加上序号、时间和本次变化的上下文
+#[derive(Debug, Clone)]
+pub struct ClipboardEvent {
+    // 单调递增的序号，从 1 开始
+    pub seq: u64,
+    // Unix 时间戳 (毫秒)
+    pub timestamp: u64,
+    pub pid: u32,
+    pub pname: String,
+    pub selection: Selection,
+    // 本次变化提供的全部格式
+    pub formats: Vec<String>,
+    pub report: ClipboardReport,
+}
+
+// 取文件后缀，转小写并添加点号用于匹配
+pub fn file_extension(path: &str) -> Option<String> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    Some(format!(".{}", ext.to_lowercase()))
+}
+
+// 单个文件的类型判断结果
+#[derive(Debug, Clone)]
+pub struct FileClass {
+    // 声明的后缀 (带点号、小写)，没有后缀时为空串
+    pub extension: String,
+    // 按文件头识别出的内容类型，文件不可读或无法识别时为 None
+    pub content: Option<&'static ContentType>,
+    // 匹配到的分类名称
+    pub category: Option<String>,
+}
+
+impl FileClass {
+    // 识别出的内容与声明的后缀不一致
+    pub fn mismatch(&self) -> bool {
+        self.content.is_some_and(|c| !c.matches_extension(&self.extension))
+    }
+}
+
+// 同时根据后缀和文件头判断文件分类：能识别出内容时以内容为准，
+// 这样改了后缀的表格、没有后缀的图片也能被发现；识别不出时退回到声明的后缀
+fn classify_with(rules: &FileRules, path: &str) -> FileClass {
+    let extension = file_extension(path).unwrap_or_default();
+    let content = sniff_file(path);
+    let category = rules.classify(&extension, content).map(|c| c.name.clone());
+    FileClass { extension, content, category }
+}
+
+// 按当前规则判断文件分类
+pub fn classify_file(path: &str) -> FileClass {
+    classify_with(&file_rules(), path)
+}
+
+// 一批复制的文件共享的规则、压缩包额度和事件上下文
+struct FileCheck<'a> {
+    rules: &'a FileRules,
+    path_rules: &'a PathRules,
+    archive_budget: ArchiveBudget,
+    report_unmatched: bool,
+    pid: u32,
+    pname: &'a str,
+    selection: Selection,
+}
+
+impl FileCheck<'_> {
+    // 匹配分类时生成 FileInfo，开启 report_unmatched 时没有匹配的文件归入 OTHER
+    // archive_entry 为压缩包中的条目路径，此时 path 是压缩包的路径；metadata 只在上报时读取
+    fn file_info(
+        &self,
+        path: &str,
+        name: String,
+        class: FileClass,
+        directory: Option<&str>,
+        archive_entry: Option<String>,
+        metadata: impl FnOnce() -> FileMetadata,
+    ) -> Option<FileInfo> {
+        let mismatch = class.mismatch();
+        let category = class.category.or_else(|| self.report_unmatched.then(|| OTHER_CATEGORY.to_string()))?;
+        Some(FileInfo {
+            name,
+            path: path.to_string(),
+            category,
+            extension: class.extension,
+            mime_type: class.content.map(|c| c.mime.to_string()),
+            extension_mismatch: mismatch,
+            directory: directory.map(str::to_string),
+            archive_entry,
+            metadata: metadata(),
+            pname: self.pname.to_string(),
+            pid: self.pid,
+            selection: self.selection,
+        })
+    }
+
+    // 判断单个文件，匹配分类的加入 report，是压缩包时再列出其中的文件
+    // directory 为找到该文件的复制的文件夹，deadline 为该文件夹的遍历截止时间；返回文件本身上报的分类
+    fn check_file(
+        &self,
+        path: &Path,
+        directory: Option<&str>,
+        deadline: Option<Instant>,
+        report: &mut FilesInfo,
+    ) -> Option<String> {
+        let path_str = path.to_string_lossy();
+        let class = classify_with(self.rules, &path_str);
+        if class.mismatch() {
+            report_info_log!("extension {:?} does not match content {}: {}",
+                class.extension, class.content.map_or("", |c| c.mime), path.display());
+        }
+        let content = class.content;
+        let file_name = path.file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let info = self.file_info(&path_str, file_name, class, directory, None, || file_metadata(path));
+        let category = info.as_ref().map(|info| info.category.clone());
+        report.files.extend(info);
+
+        let format = content
+            .filter(|_| self.archive_budget.max_depth > 0)
+            .and_then(|content| file_archive_format(path, content));
+        if let Some(format) = format {
+            self.check_archive(path, format, directory, deadline, report);
+        }
+        category
+    }
+
+    // 列出压缩包，条目按同样的分类规则判断
+    // 路径规则按 "压缩包路径/条目路径" 判断，嵌套压缩包之间的 "!/" 当作目录分隔符
+    fn check_archive(
+        &self,
+        path: &Path,
+        format: ArchiveFormat,
+        directory: Option<&str>,
+        deadline: Option<Instant>,
+        report: &mut FilesInfo,
+    ) {
+        let path_str = path.to_string_lossy();
+        let mut categories = BTreeMap::new();
+        let listing = list_archive(path, format, &self.archive_budget, deadline, |entry| {
+            let entry_path = format!("{}/{}", path_str, entry.path.replace("!/", "/"));
+            if self.path_rules.reject_reason(&entry_path).is_some() {
+                return;
+            }
+            let extension = file_extension(&entry.path).unwrap_or_default();
+            let category = self.rules.classify(&extension, entry.content).map(|c| c.name.clone());
+            let class = FileClass { extension, content: entry.content, category };
+            let name = entry.path.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+            let metadata = || FileMetadata { size: entry.size, ..Default::default() };
+            if let Some(info) = self.file_info(&path_str, name, class, directory, Some(entry.path), metadata) {
+                *categories.entry(info.category.clone()).or_default() += 1;
+                report.files.push(info);
+            }
+        });
+        if let Some(error) = &listing.error {
+            report_error_log!("failed to read archive {}: {}", path.display(), error);
+        }
+        report_info_log!(">> ALERT: User copied ARCHIVE {} ({} entries, matched {:?}{})", path.display(), listing.entries,
+            categories, listing.limit.map(|l| format!(", stopped at {:?} limit", l)).unwrap_or_default());
+        report.archives.push(ArchiveInfo {
+            path: path_str.into_owned(),
+            format,
+            entries: listing.entries,
+            categories,
+            limit: listing.limit,
+            error: listing.error,
+        });
+    }
+}
+
+// 核心逻辑 1: 分析文件列表 (CF_HDROP)，复制的文件夹会被遍历，压缩包会被列出
+pub fn check_dropped_files(paths: &[String], pid: u32, pname: &str, selection: Selection) -> FilesInfo {
+    let mut report = FilesInfo::default();
+    // 同一批文件使用同一版本的规则
+    let rules = file_rules();
+    let path_filter = path_rules();
+    let mut budget = WalkBudget::current();
+    let check = FileCheck {
+        rules: &rules,
+        path_rules: &path_filter,
+        archive_budget: ArchiveBudget::current(),
+        report_unmatched: REPORT_UNMATCHED.load(Ordering::Relaxed),
+        pid,
+        pname,
+        selection,
+    };
+    report_info_log!("detected file count: {}", paths.len());
+    for path_str in paths {
+        let path = Path::new(path_str);
+
+        // 文件夹不按文件判断；深度为 0 时不遍历，只上报文件夹本身
+        if path.is_dir() {
+            // 文件夹本身只检查排除规则，包含规则对其中的每个文件生效
+            if let Some(pattern) = path_filter.excluded_by(path_str) {
+                report_info_log!("skip {}: excluded by path rule {:?}", path.display(), pattern.source);
+                continue;
+            }
+            let mut directory = DirectoryInfo {
+                path: path_str.clone(),
+                files: 0,
+                categories: BTreeMap::new(),
+                limit: None,
+            };
+            let deadline = budget.deadline;
+            let (scanned, limit) = if budget.max_depth == 0 {
+                (0, Some(WalkLimit::DEPTH))
+            } else {
+                walk_directory(
+                    path,
+                    &mut budget,
+                    |dir| path_filter.excluded_by(&dir.to_string_lossy()).is_some(),
+                    |file| {
+                        if path_filter.reject_reason(&file.to_string_lossy()).is_some() {
+                            return;
+                        }
+                        if let Some(category) = check.check_file(file, Some(path_str), Some(deadline), &mut report) {
+                            *directory.categories.entry(category).or_default() += 1;
+                        }
+                    },
+                )
+            };
+            directory.files = scanned;
+            directory.limit = limit;
+            report_info_log!(">> ALERT: User copied FOLDER {} ({} files, matched {:?}{})", path.display(), scanned,
+                directory.categories, limit.map(|l| format!(", stopped at {:?} limit", l)).unwrap_or_default());
+            report.directories.push(directory);
+            continue;
+        }
+
+        // 先按路径过滤，被排除的文件不再读取文件头
+        if let Some(reason) = path_filter.reject_reason(path_str) {
+            report_info_log!("skip {}: {}", path.display(), reason);
+            continue;
+        }
+
+        // 如果是关注的文件类型，已经推入数组
+        let detected_msg = match check.check_file(path, None, None, &mut report) {
+            Some(category) => format!(">> ALERT: User copied {} FILE(S): {}", category, path.display()),
+            None => String::from("no file detected"),
+        };
+        report_info_log!("{}", detected_msg);
+    }
+    report
+}
+
+// 读取文件列表：Windows 为 CF_HDROP，X11 / Wayland 为 URI 列表
+// 只有存在文件列表格式且其中有本地路径时才返回 Some
+fn read_file_list<B: ClipboardBackend + ?Sized>(backend: &mut B) -> Option<Vec<String>> {
+    if backend.has_format(FORMAT_HDROP) {
+        let paths = backend.read(FORMAT_HDROP)
+            .map(|bytes| parse_drop_files(&bytes))
+            .unwrap_or_default();
+        return Some(paths);
+    }
+    [FORMAT_URI_LIST, FORMAT_GNOME_FILES].iter()
+        .find_map(|f| if backend.has_format(f) { backend.read(f) } else { None })
+        .map(|bytes| parse_uri_list(&bytes))
+        .filter(|paths| !paths.is_empty())
+}
+
+// 读取并解析第一个包含表格的格式
+fn read_table<B: ClipboardBackend + ?Sized>(backend: &mut B) -> Option<(&'static str, Vec<Vec<String>>)> {
+    TABLE_FORMATS.iter().find_map(|&format| {
+        let bytes = if backend.has_format(format) { backend.read(format) } else { None }?;
+        let rows = parse_table(format, &bytes);
+        (!rows.is_empty()).then_some((format, rows))
+    })
+}
+
+// 按 MAX_TABLE_CELLS 截取单元格，行列数按完整数据统计
+fn table_info(rows: Vec<Vec<String>>, format: &str, pid: u32, pname: &str, selection: Selection) -> TableInfo {
+    let max_cells = MAX_TABLE_CELLS.load(Ordering::Relaxed);
+    let row_count = rows.len();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut remaining = max_cells;
+    let mut truncated = false;
+    let mut cells = vec![];
+    for mut row in rows {
+        if remaining == 0 {
+            truncated = true;
+            break;
+        }
+        if row.len() > remaining {
+            row.truncate(remaining);
+            truncated = true;
+        }
+        remaining -= row.len();
+        cells.push(row);
+    }
+
+    TableInfo {
+        pname: pname.to_string(),
+        pid,
+        format: format.to_string(),
+        selection,
+        rows: row_count,
+        columns,
+        cells,
+        truncated,
+    }
+}
+
+// 核心逻辑 2: 分析剪贴板内容，返回需要上报的事件
+pub fn analyze_clipboard<B: ClipboardBackend + ?Sized>(backend: &mut B) -> Option<ClipboardEvent> {
+    let selection = backend.selection();
+    // 剪贴板暂时无法读取时跳过这次变化，原因由后端记录
+    let formats = backend.formats()?;
+    let (pid, pname) = match backend.owner() {
+        Some((p, n)) => {
+            report_info_log!("Source Process: {} (PID: {}) Selection: {:?}", n, p, selection);
+            (p, n)
+        }
+        None => {
+            if backend.knows_owner() {
+                report_error_log!("Clipboard Owner: None");
+            } else {
+                report_info_log!("Clipboard Owner: unavailable on this platform");
+            }
+            (0, "Unknown".to_string())
+        }
+    };
+
+    let report = classify_clipboard(backend, &formats, pid, &pname, selection)?;
+    Some(ClipboardEvent {
+        seq: EVENT_SEQ.fetch_add(1, Ordering::SeqCst) + 1,
+        timestamp: backend.changed_at().unwrap_or_else(now_millis),
+        pid,
+        pname,
+        selection,
+        formats,
+        report,
+    })
+}
+
+fn classify_clipboard<B: ClipboardBackend + ?Sized>(
+    backend: &mut B,
+    formats: &[String],
+    pid: u32,
+    pname: &str,
+    selection: Selection,
+) -> Option<ClipboardReport> {
+    // 0. 剪贴板被清空
+    if formats.is_empty() {
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        report_info_log!(">> Clipboard cleared.");
+        return Some(ClipboardReport::Cleared);
+    }
+
+    // 1. 检查是否是文件 (CF_HDROP / text/uri-list)
+    if let Some(paths) = read_file_list(backend) {
+        let files = check_dropped_files(&paths, pid, pname, selection);
+        // 文件夹没有遍历完时即使没有匹配的文件也上报，提示结果不完整
+        return (!files.files.is_empty() || files.truncated()).then_some(ClipboardReport::Files(files));
+    }
+
+    // 2. 检查是否是表格数据 (CSV / HTML)，不含表格的 HTML (如网页上的一段文字) 不算
+    // Excel / LibreOffice 复制单元格时同时提供位图，因此表格先于图片检查
+    if let Some((format, rows)) = read_table(backend) {
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        let table = table_info(rows, format, pid, pname, selection);
+        report_info_log!(">> ALERT: User copied TABLE DATA ({} rows x {} columns, {}).", table.rows, table.columns, format);
+        return Some(ClipboardReport::Table(table));
+    }
+
+    // 3. 检查是否是图片内容 (Bitmap / image/*)
+    if backend.has_format(FORMAT_BITMAP) || IMAGE_FORMATS.iter().any(|f| backend.has_format(f)) {
+        // 如果没有获取到数据（比如只有 handle 但 lock 失败），也不上报
+        let image_data = IMAGE_FORMATS.iter()
+            .find_map(|f| {
+                let data = if backend.has_format(f) { backend.read(f) } else { None };
+                data.filter(|data| !data.is_empty())
+            })
+            .unwrap_or_default();
+        let data_size = image_data.len();
+        if data_size == 0 {
+            return None;
+        }
+
+        // === 防重复过滤核心逻辑 ===
+        // 如果大小相同，大概率是关闭程序导致的 Flush
+        let last_size = LAST_IMG_SIZE.swap(data_size, Ordering::Relaxed);
+        if data_size == last_size {
+            IMAGES_DEDUPLICATED.fetch_add(1, Ordering::Relaxed);
+            report_info_log!(">> Filtered duplicate image event (Same Size: {} bytes)", data_size);
+            return None;
+        }
+
+        report_info_log!(">> ALERT: Captured Image. Size: {} bytes. Process: {}", data_size, pname);
+        return Some(ClipboardReport::Shot(ShotInfo {
+            pname: pname.to_string(),
+            pid,
+            data: image_data,
+            selection,
+        }));
+    }
+    // 如果不是图片，重置图片大小记录
+    LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+
+    // 4. 检查是否是纯文本，空文本不上报
+    let (format, text) = TEXT_FORMATS.iter().find_map(|&format| {
+        let bytes = if backend.has_format(format) { backend.read(format) } else { None }?;
+        Some((format, decode_text(format, &bytes)))
+    })?;
+    if text.is_empty() {
+        return None;
+    }
+    let preview_chars = TEXT_PREVIEW_CHARS.load(Ordering::Relaxed);
+    let info = text_info(&text, preview_chars, format, pid, pname, selection);
+    if DETECT_CODE.load(Ordering::Relaxed) {
+        if let Some(guess) = detect_language(&text) {
+            report_info_log!(
+                ">> ALERT: User copied CODE ({:?}, confidence {:.2}, {} lines).",
+                guess.language,
+                guess.confidence,
+                info.lines
+            );
+            return Some(ClipboardReport::Code(CodeInfo { text: info, language: guess.language, confidence: guess.confidence }));
+        }
+    }
+    report_info_log!(">> ALERT: User copied TEXT ({} chars, {} lines).", info.chars, info.lines);
+    Some(ClipboardReport::Text(info))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::backend::{ClipboardBackend, FORMAT_CSV, FORMAT_DIB, FORMAT_HTML};
+    use super::mock::{MockBackend, MockSnapshot};
+    use super::filter::EventKind;
+    use super::pattern::PathPattern;
+    use super::*;
+    use crate::global::{set_file_rules, set_path_rules, DEFAULT_MAX_WALK_DEPTH, MAX_WALK_DEPTH};
+    use crate::utils::{lock_globals, test_dir};
+
+    // 回放一个快照并分析
+    fn analyze(snapshot: MockSnapshot) -> Option<ClipboardEvent> {
+        let mut backend: MockBackend = [snapshot].into_iter().collect();
+        assert!(backend.wait_for_change());
+        analyze_clipboard(&mut backend)
+    }
+
+    // 依次回放全部快照，返回每个快照的分析结果
+    fn analyze_all(snapshots: Vec<MockSnapshot>) -> Vec<Option<ClipboardReport>> {
+        let mut backend: MockBackend = snapshots.into_iter().collect();
+        let mut reports = vec![];
+        while backend.wait_for_change() {
+            reports.push(analyze_clipboard(&mut backend).map(|event| event.report));
+        }
+        reports
+    }
+
+    #[test]
+    fn hdrop_files_are_classified() {
+        let _globals = lock_globals();
+        set_file_rules(FileRules::default());
+        set_path_rules(PathRules::default());
+        let dir = test_dir("hdrop");
+        let paths: Vec<String> = ["report.xlsx", "main.rs", "notes.txt"]
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                fs::write(&path, "plain text").unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+
+        let event = analyze(MockSnapshot::files(&paths).with_owner(7, "explorer.exe")).expect("files event");
+        let ClipboardReport::Files(files) = event.report else {
+            panic!("expected files, got {:?}", event.report);
+        };
+        let found: Vec<(&str, &str)> = files.files.iter().map(|f| (f.name.as_str(), f.category.as_str())).collect();
+        assert_eq!(found, vec![("report.xlsx", "EXCEL"), ("main.rs", "CODE")]);
+        assert_eq!(files.files[0].path, paths[0]);
+        assert_eq!(files.files[0].pname, "explorer.exe");
+        assert_eq!(files.files[0].metadata.size, Some(10));
+        assert!(!files.truncated());
+        assert_eq!(event.formats, vec![FORMAT_HDROP]);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn repeated_image_of_same_size_is_deduplicated() {
+        let _globals = lock_globals();
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        let text = || MockSnapshot::new().with_format(FORMAT_TEXT_MIME, "hello");
+        let reports = analyze_all(vec![
+            MockSnapshot::image(vec![1u8; 64]),
+            MockSnapshot::image(vec![2u8; 64]),
+            MockSnapshot::image(vec![3u8; 65]),
+            text(),
+            MockSnapshot::image(vec![3u8; 65]),
+        ]);
+
+        let kinds: Vec<Option<EventKind>> = reports.iter().map(|r| r.as_ref().map(ClipboardReport::kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![Some(EventKind::IMAGE), None, Some(EventKind::IMAGE), Some(EventKind::TEXT), Some(EventKind::IMAGE)]
+        );
+        let Some(ClipboardReport::Shot(shot)) = &reports[0] else { unreachable!() };
+        assert_eq!(shot.data, vec![1u8; 64]);
+    }
+
+    #[test]
+    fn empty_clipboard_is_cleared() {
+        let _globals = lock_globals();
+        LAST_IMG_SIZE.store(64, Ordering::Relaxed);
+        let event = analyze(MockSnapshot::new()).expect("cleared event");
+        assert!(matches!(event.report, ClipboardReport::Cleared));
+        assert!(event.formats.is_empty());
+        assert_eq!(LAST_IMG_SIZE.load(Ordering::Relaxed), 0);
+    }
+
+    // 剪贴板一直被其他程序占用的后端
+    struct Locked;
+
+    impl ClipboardBackend for Locked {
+        fn formats(&mut self) -> Option<Vec<String>> {
+            None
+        }
+
+        fn read(&mut self, _format: &str) -> Option<Vec<u8>> {
+            None
+        }
+
+        fn owner(&mut self) -> Option<(u32, String)> {
+            None
+        }
+
+        fn wait_for_change(&mut self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn unavailable_clipboard_is_not_cleared() {
+        let _globals = lock_globals();
+        assert!(analyze_clipboard(&mut Locked).is_none());
+    }
+
+    #[test]
+    fn spreadsheet_copy_with_bitmap_is_a_table() {
+        let _globals = lock_globals();
+        let html = "Version:0.9\r\n<table><tr><td>a</td><td>b</td></tr><tr><td>1</td><td>2</td></tr></table>";
+        let snapshot = MockSnapshot::new()
+            .with_format(FORMAT_CSV, "a,b\r\n1,2\r\n")
+            .with_format(FORMAT_HTML, html)
+            .with_format(FORMAT_DIB, vec![1u8; 64])
+            .with_owner(42, "EXCEL.EXE");
+
+        let event = analyze(snapshot).expect("table event");
+        let ClipboardReport::Table(table) = event.report else {
+            panic!("expected a table, got {:?}", event.report);
+        };
+        assert_eq!((table.rows, table.columns), (2, 2));
+        assert_eq!(table.cells, vec![vec!["a", "b"], vec!["1", "2"]]);
+        assert_eq!(table.pname, "EXCEL.EXE");
+    }
+
+    #[test]
+    fn table_cells_are_truncated_at_max_table_cells() {
+        let _globals = lock_globals();
+        let rows = vec![vec![String::from("x"); 3]; 4];
+        MAX_TABLE_CELLS.store(7, Ordering::Relaxed);
+        let table = table_info(rows, FORMAT_CSV, 1, "test", Selection::CLIPBOARD);
+        MAX_TABLE_CELLS.store(crate::global::DEFAULT_MAX_TABLE_CELLS, Ordering::Relaxed);
+
+        assert_eq!((table.rows, table.columns), (4, 3));
+        assert!(table.truncated);
+        assert_eq!(table.cells.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 3, 1]);
+    }
+
+    #[test]
+    fn path_rules_apply_to_archive_entries() {
+        let _globals = lock_globals();
+        set_file_rules(FileRules::default());
+        let dir = test_dir("archive-rules");
+        let mut inner = ZipWriter::new(Cursor::new(vec![]));
+        inner.start_file("node_modules/pkg/index.js", SimpleFileOptions::default()).unwrap();
+        inner.start_file("src/lib.rs", SimpleFileOptions::default()).unwrap();
+        let inner = inner.finish().unwrap().into_inner();
+        let mut outer = ZipWriter::new(fs::File::create(dir.join("x.zip")).unwrap());
+        for (name, data) in [("node_modules/a.js", &b"a"[..]), ("app/main.js", b"b"), ("nested.zip", &inner)] {
+            outer.start_file(name, SimpleFileOptions::default()).unwrap();
+            outer.write_all(data).unwrap();
+        }
+        outer.finish().unwrap();
+        let path = dir.join("x.zip").to_string_lossy().into_owned();
+
+        set_path_rules(PathRules { include: vec![], exclude: vec![PathPattern::glob("**/node_modules", true).unwrap()] });
+        let report = check_dropped_files(std::slice::from_ref(&path), 0, "", Selection::CLIPBOARD);
+        set_path_rules(PathRules::default());
+        let _ = fs::remove_dir_all(dir);
+
+        let entries: Vec<&str> = report.files.iter().filter_map(|file| file.archive_entry.as_deref()).collect();
+        assert_eq!(entries, vec!["app/main.js", "nested.zip!/src/lib.rs"]);
+        // 被排除的条目仍然算作列出的条目
+        assert_eq!(report.archives[0].entries, 5);
+    }
+
+    #[test]
+    fn folders_are_not_walked_at_depth_zero() {
+        let _globals = lock_globals();
+        set_file_rules(FileRules::default());
+        set_path_rules(PathRules::default());
+        let dir = test_dir("walk-depth-zero");
+        let folder = dir.join("exports");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("report.xlsx"), "plain text").unwrap();
+        let path = folder.to_string_lossy().into_owned();
+
+        MAX_WALK_DEPTH.store(0, Ordering::Relaxed);
+        REPORT_UNMATCHED.store(true, Ordering::Relaxed);
+        let report = check_dropped_files(std::slice::from_ref(&path), 0, "", Selection::CLIPBOARD);
+        MAX_WALK_DEPTH.store(DEFAULT_MAX_WALK_DEPTH, Ordering::Relaxed);
+        REPORT_UNMATCHED.store(false, Ordering::Relaxed);
+        let _ = fs::remove_dir_all(dir);
+
+        // 文件夹本身既不算作文件，也不遍历其中的文件
+        assert!(report.files.is_empty(), "{:?}", report.files);
+        assert_eq!(report.directories.len(), 1);
+        assert_eq!(report.directories[0].path, path);
+        assert_eq!(report.directories[0].files, 0);
+        assert!(report.directories[0].categories.is_empty());
+        assert_eq!(report.directories[0].limit, Some(WalkLimit::DEPTH));
+    }
+}
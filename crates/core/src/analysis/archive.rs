@@ -0,0 +1,791 @@
+// 列出复制的压缩包中的文件 (zip / tar / tar.gz / 7z)，只在内存中读取，不解压到磁盘
+// 条目按文件头识别内容，嵌套的压缩包读入内存继续列出；解压的字节数、条目数和嵌套深度都有上限，防止压缩炸弹
+// 单独读取的条目 (zip、tar) 开头用于识别的读取另有 SNIFF_BYTES 的额度，用完后只列出名称，不计入解压的字节数
+// 7z 只按目录列出文件名，不读取条目内容
+// zip 的中央目录和 7z 的文件头要整个解析后才能列出，解析前先按 EOCD / 起始头中记录的条目数和大小检查额度
+
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use super::sniff::{self, sniff, ContentType, HEADER_BYTES};
+use crate::global::{MAX_ARCHIVE_BYTES, MAX_ARCHIVE_DEPTH, MAX_ARCHIVE_ENTRIES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    ZIP,
+    TAR,
+    TARGZ,
+    SEVENZIP,
+}
+
+// 列出提前结束或不完整的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveLimit {
+    // 有嵌套的压缩包超过了最大深度，没有打开
+    DEPTH,
+    // 列出的条目数达到上限，或压缩包目录中的条目数超过剩余额度
+    ENTRIES,
+    // 解压的字节数 (包括 zip / 7z 的目录) 达到上限
+    BYTES,
+    // 在复制的文件夹中找到的压缩包，列出时文件夹的遍历时间用完
+    TIME,
+}
+
+// 一次剪贴板变化中识别单独读取的条目内容最多读取的字节数
+const SNIFF_BYTES: u64 = 16 * 1024 * 1024;
+
+// 7z 文件头中的属性 ID
+#[cfg(not(target_arch = "wasm32"))]
+const K_END: u8 = 0x00;
+#[cfg(not(target_arch = "wasm32"))]
+const K_PACK_INFO: u8 = 0x06;
+#[cfg(not(target_arch = "wasm32"))]
+const K_UNPACK_INFO: u8 = 0x07;
+#[cfg(not(target_arch = "wasm32"))]
+const K_SIZE: u8 = 0x09;
+#[cfg(not(target_arch = "wasm32"))]
+const K_CRC: u8 = 0x0A;
+#[cfg(not(target_arch = "wasm32"))]
+const K_FOLDER: u8 = 0x0B;
+#[cfg(not(target_arch = "wasm32"))]
+const K_CODERS_UNPACK_SIZE: u8 = 0x0C;
+#[cfg(not(target_arch = "wasm32"))]
+const K_ENCODED_HEADER: u8 = 0x17;
+
+// 一次剪贴板变化中全部压缩包共享的额度
+#[derive(Debug)]
+pub struct ArchiveBudget {
+    // 复制的压缩包中的条目深度为 1，0 表示不列出压缩包
+    pub max_depth: usize,
+    entries_left: Cell<usize>,
+    bytes_left: Cell<u64>,
+    sniff_left: Cell<u64>,
+}
+
+impl ArchiveBudget {
+    // 按当前配置创建
+    pub fn current() -> Self {
+        Self {
+            max_depth: MAX_ARCHIVE_DEPTH.load(Ordering::Relaxed),
+            entries_left: Cell::new(MAX_ARCHIVE_ENTRIES.load(Ordering::Relaxed)),
+            bytes_left: Cell::new(MAX_ARCHIVE_BYTES.load(Ordering::Relaxed)),
+            sniff_left: Cell::new(SNIFF_BYTES),
+        }
+    }
+}
+
+// 压缩包中的一个文件
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    // 在压缩包中的路径，嵌套的压缩包之间用 "!/" 分隔，如 "src.zip!/lib/a.rs"
+    pub path: String,
+    // 按条目开头识别出的内容类型，没有读取内容 (加密、7z) 或无法识别时为 None
+    pub content: Option<&'static ContentType>,
+    // 条目头中记录的解压后大小
+    pub size: Option<u64>,
+}
+
+// 列出的结果
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveListing {
+    // 列出的文件数 (包括嵌套压缩包中的)
+    pub entries: usize,
+    pub limit: Option<ArchiveLimit>,
+    // 第一个读取错误 (损坏、不支持的格式)，出错前列出的条目仍然有效
+    pub error: Option<String>,
+}
+
+// 按识别出的内容判断是否是支持的压缩包，gzip 需要解压开头确认里面是 tar
+fn archive_format(content: &ContentType, header: &[u8]) -> Option<ArchiveFormat> {
+    if *content == sniff::ZIP {
+        Some(ArchiveFormat::ZIP)
+    } else if *content == sniff::TAR {
+        Some(ArchiveFormat::TAR)
+    } else if *content == sniff::SEVEN_Z {
+        Some(ArchiveFormat::SEVENZIP)
+    } else if *content == sniff::GZIP {
+        let mut block = [0u8; 512];
+        GzDecoder::new(header).read_exact(&mut block).ok()?;
+        (&block[257..262] == b"ustar").then_some(ArchiveFormat::TARGZ)
+    } else {
+        None
+    }
+}
+
+// 判断磁盘上的文件是否是支持的压缩包，content 为已识别出的内容类型
+pub fn file_archive_format(path: &Path, content: &ContentType) -> Option<ArchiveFormat> {
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    File::open(path).ok()?.take(HEADER_BYTES as u64).read_to_end(&mut header).ok()?;
+    archive_format(content, &header)
+}
+
+// 列出磁盘上的压缩包，对每个文件条目 (包括嵌套的压缩包本身) 调用 visit
+// deadline 为所在文件夹的遍历截止时间，每个条目和每次读取前检查
+pub fn list_archive(
+    path: &Path,
+    format: ArchiveFormat,
+    budget: &ArchiveBudget,
+    deadline: Option<Instant>,
+    visit: impl FnMut(ArchiveEntry),
+) -> ArchiveListing {
+    let mut lister = Lister { budget, deadline, visit, listing: ArchiveListing::default() };
+    let result = File::open(path)
+        .map_err(|e| Stop::Error(e.to_string()))
+        .and_then(|file| lister.list(file, format, "", 1));
+    match result {
+        Ok(()) => {}
+        Err(Stop::Limit(limit)) => lister.listing.limit = Some(limit),
+        Err(Stop::Error(error)) => {
+            lister.listing.error.get_or_insert(error);
+        }
+    }
+    lister.listing
+}
+
+// 中止列出的原因
+enum Stop {
+    Limit(ArchiveLimit),
+    Error(String),
+}
+
+fn expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+// 按剩余额度计数的读取器，额度或时间用完后读取报错；left 为 None 时只检查时间
+struct Metered<'a, R> {
+    inner: R,
+    left: Option<&'a Cell<u64>>,
+    deadline: Option<Instant>,
+}
+
+impl<R: Read> Read for Metered<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if expired(self.deadline) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "walk time limit reached"));
+        }
+        let Some(left_cell) = self.left else {
+            return self.inner.read(buf);
+        };
+        let left = left_cell.get();
+        if left == 0 {
+            return Err(io::Error::other("archive byte limit reached"));
+        }
+        let max = buf.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        left_cell.set(left - n as u64);
+        Ok(n)
+    }
+}
+
+// 条目内容的来源
+enum EntryData<'r> {
+    // 不读取内容 (加密、压缩方法不支持)
+    Unread,
+    // 从整个压缩包的解压流中读取，读到的字节已经计入额度 (tar.gz)
+    Stream(&'r mut dyn Read),
+    // 单独读取的条目 (zip、未压缩的 tar)：开头的识别读取计入识别额度，嵌套的压缩包读入内存时计入解压额度
+    Separate(&'r mut dyn Read),
+}
+
+impl<R: Seek> Seek for Metered<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+struct Lister<'a, F> {
+    budget: &'a ArchiveBudget,
+    deadline: Option<Instant>,
+    visit: F,
+    listing: ArchiveListing,
+}
+
+impl<F: FnMut(ArchiveEntry)> Lister<'_, F> {
+    // 读取出错时，额度或时间用完的算作达到限制
+    fn io_stop(&self, error: io::Error) -> Stop {
+        if expired(self.deadline) {
+            Stop::Limit(ArchiveLimit::TIME)
+        } else if self.budget.bytes_left.get() == 0 {
+            Stop::Limit(ArchiveLimit::BYTES)
+        } else {
+            Stop::Error(error.to_string())
+        }
+    }
+
+    // prefix 为嵌套压缩包的路径前缀，depth 为条目的深度
+    fn list<R: Read + Seek>(&mut self, reader: R, format: ArchiveFormat, prefix: &str, depth: usize) -> Result<(), Stop> {
+        let (left, deadline) = (&self.budget.bytes_left, self.deadline);
+        match format {
+            ArchiveFormat::ZIP => self.list_zip(reader, prefix, depth),
+            ArchiveFormat::TAR => {
+                // 未压缩的 tar 不会放大，条目头不计入额度；可定位时跳过条目内容不需要读取
+                let mut archive = tar::Archive::new(Metered { inner: reader, left: None, deadline });
+                let entries = archive.entries_with_seek().map_err(|e| self.io_stop(e))?;
+                self.list_tar(entries, false, prefix, depth)
+            }
+            ArchiveFormat::TARGZ => {
+                // 解压出的全部字节都计入额度，包括跳过的条目内容
+                let mut archive = tar::Archive::new(Metered { inner: GzDecoder::new(reader), left: Some(left), deadline });
+                let entries = archive.entries().map_err(|e| self.io_stop(e))?;
+                self.list_tar(entries, true, prefix, depth)
+            }
+            ArchiveFormat::SEVENZIP => self.list_7z(reader, prefix, depth),
+        }
+    }
+
+    // 解析目录前预留额度：条目数超过剩余额度时不解析，目录本身的字节数计入解压额度
+    fn reserve(&self, entries: u64, bytes: u64) -> Result<(), Stop> {
+        if entries > self.budget.entries_left.get() as u64 {
+            return Err(Stop::Limit(ArchiveLimit::ENTRIES));
+        }
+        let bytes_left = self.budget.bytes_left.get();
+        if bytes > bytes_left {
+            return Err(Stop::Limit(ArchiveLimit::BYTES));
+        }
+        self.budget.bytes_left.set(bytes_left - bytes);
+        Ok(())
+    }
+
+    fn list_zip<R: Read + Seek>(&mut self, mut reader: R, prefix: &str, depth: usize) -> Result<(), Stop> {
+        // 找不到 EOCD 时交给 ZipArchive 报告错误
+        if let Some((entries, directory_size)) = zip_directory(&mut reader) {
+            self.reserve(entries, directory_size)?;
+        }
+        let mut archive = ZipArchive::new(reader).map_err(|e| Stop::Error(e.to_string()))?;
+        for index in 0..archive.len() {
+            let Some(name) = archive.name_for_index(index).map(str::to_string) else {
+                continue;
+            };
+            if name.ends_with('/') {
+                continue;
+            }
+            // 加密或压缩方法不支持的条目只按名称判断
+            match archive.by_index(index) {
+                Ok(mut file) => {
+                    let size = file.size();
+                    self.entry(&name, Some(size), EntryData::Separate(&mut file), prefix, depth)?
+                }
+                Err(_) => self.entry(&name, None, EntryData::Unread, prefix, depth)?,
+            }
+        }
+        Ok(())
+    }
+
+    // stream 为 true 时条目从整个压缩包的解压流中读取
+    fn list_tar<R: Read>(&mut self, entries: tar::Entries<'_, R>, stream: bool, prefix: &str, depth: usize) -> Result<(), Stop> {
+        for entry in entries {
+            let mut entry = entry.map_err(|e| self.io_stop(e))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+            let size = entry.size();
+            let data = if stream { EntryData::Stream(&mut entry) } else { EntryData::Separate(&mut entry) };
+            self.entry(&name, Some(size), data, prefix, depth)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn list_7z<R: Read + Seek>(&mut self, mut reader: R, prefix: &str, depth: usize) -> Result<(), Stop> {
+        let len = reader.seek(SeekFrom::End(0)).map_err(|e| self.io_stop(e))?;
+        reader.rewind().map_err(|e| self.io_stop(e))?;
+        // 起始头只记录文件头的大小，条目数要解析文件头后才知道
+        let mut start = [0u8; 32];
+        reader.read_exact(&mut start).map_err(|e| Stop::Error(e.to_string()))?;
+        let header_offset = u64::from_le_bytes(start[12..20].try_into().unwrap());
+        let header_size = u64::from_le_bytes(start[20..28].try_into().unwrap());
+        self.reserve(0, header_size)?;
+        // 编码 (压缩) 的文件头要先解压，按其中记录的压缩前后大小检查额度
+        let mut header = vec![];
+        reader.seek(SeekFrom::Start(32u64.saturating_add(header_offset))).map_err(|e| self.io_stop(e))?;
+        Read::take(&mut reader, header_size).read_to_end(&mut header).map_err(|e| self.io_stop(e))?;
+        if header.first() == Some(&K_ENCODED_HEADER) {
+            let (packed, unpacked) =
+                encoded_header_sizes(&header[1..]).ok_or_else(|| Stop::Error(String::from("invalid 7z encoded header")))?;
+            self.reserve(0, packed.saturating_add(unpacked))?;
+        }
+        reader.rewind().map_err(|e| self.io_stop(e))?;
+        let archive = sevenz_rust::Archive::read(&mut reader, len, &[]).map_err(|e| Stop::Error(e.to_string()))?;
+        for file in archive.files.iter().filter(|file| !file.is_directory()) {
+            self.entry(file.name(), Some(file.size()), EntryData::Unread, prefix, depth)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn list_7z<R: Read + Seek>(&mut self, _reader: R, _prefix: &str, _depth: usize) -> Result<(), Stop> {
+        Err(Stop::Error(String::from("7z archives are not supported on this platform")))
+    }
+
+    // 处理一个文件条目：读取开头识别内容，是压缩包且没超过嵌套深度时整个读入内存继续列出
+    fn entry(
+        &mut self,
+        name: &str,
+        size: Option<u64>,
+        data: EntryData<'_>,
+        prefix: &str,
+        depth: usize,
+    ) -> Result<(), Stop> {
+        if expired(self.deadline) {
+            return Err(Stop::Limit(ArchiveLimit::TIME));
+        }
+        let entries_left = self.budget.entries_left.get();
+        if entries_left == 0 {
+            return Err(Stop::Limit(ArchiveLimit::ENTRIES));
+        }
+        self.budget.entries_left.set(entries_left - 1);
+        self.listing.entries += 1;
+
+        let path = format!("{}{}", prefix, name.trim_start_matches("./").trim_start_matches('/'));
+        let (reader, separate) = match data {
+            EntryData::Unread => {
+                (self.visit)(ArchiveEntry { path, content: None, size });
+                return Ok(());
+            }
+            EntryData::Stream(reader) => (reader, false),
+            EntryData::Separate(reader) => (reader, true),
+        };
+        let sniff_left = self.budget.sniff_left.get();
+        let sniff_bytes = if separate { sniff_left.min(HEADER_BYTES as u64) } else { HEADER_BYTES as u64 };
+        // 识别额度用完后只列出名称
+        if sniff_bytes == 0 {
+            (self.visit)(ArchiveEntry { path, content: None, size });
+            return Ok(());
+        }
+        let mut data = Vec::new();
+        let read = Read::take(&mut *reader, sniff_bytes).read_to_end(&mut data);
+        if separate {
+            self.budget.sniff_left.set(sniff_left - data.len() as u64);
+        }
+        if let Err(error) = read {
+            // 单个条目损坏时继续列出其余条目
+            match self.io_stop(error) {
+                Stop::Error(error) => {
+                    self.listing.error.get_or_insert(format!("{}: {}", path, error));
+                    (self.visit)(ArchiveEntry { path, content: None, size });
+                    return Ok(());
+                }
+                stop => return Err(stop),
+            }
+        }
+        let content = sniff(&mut Cursor::new(&data));
+        let nested = content.and_then(|content| archive_format(content, &data));
+        (self.visit)(ArchiveEntry { path: path.clone(), content, size });
+
+        let Some(format) = nested else {
+            return Ok(());
+        };
+        if depth >= self.budget.max_depth {
+            self.listing.limit.get_or_insert(ArchiveLimit::DEPTH);
+            return Ok(());
+        }
+        let read = if separate {
+            // 识别时读取的开头同样计入解压额度
+            self.reserve(0, data.len() as u64)?;
+            Metered { inner: reader, left: Some(&self.budget.bytes_left), deadline: self.deadline }.read_to_end(&mut data)
+        } else {
+            reader.read_to_end(&mut data)
+        };
+        read.map_err(|e| self.io_stop(e))?;
+        match self.list(Cursor::new(data), format, &format!("{}!/", path), depth + 1) {
+            // 嵌套的压缩包损坏时不影响外层
+            Err(Stop::Error(error)) => {
+                self.listing.error.get_or_insert(format!("{}: {}", path, error));
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+// 按顺序读取 7z 文件头
+#[cfg(not(target_arch = "wasm32"))]
+struct HeaderReader<'a> {
+    bytes: &'a [u8],
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HeaderReader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&first, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        Some(first)
+    }
+
+    fn skip(&mut self, count: u64) -> Option<()> {
+        let count = usize::try_from(count).ok()?;
+        self.bytes = self.bytes.get(count..)?;
+        Some(())
+    }
+
+    // 7z 的变长整数：首字节开头 1 的个数为后续字节数，剩余的位是最高部分
+    fn number(&mut self) -> Option<u64> {
+        let first = self.byte()?;
+        let mut value = 0u64;
+        for i in 0..8 {
+            let mask = 0x80u8 >> i;
+            if first & mask == 0 {
+                return Some(value | (u64::from(first & (mask - 1)) << (8 * i)));
+            }
+            value |= u64::from(self.byte()?) << (8 * i);
+        }
+        Some(value)
+    }
+
+    // 跳过 count 个 CRC：先是全部存在的标志，否则是存在位图
+    fn skip_digests(&mut self, count: u64) -> Option<()> {
+        let defined = if self.byte()? != 0 {
+            count
+        } else {
+            let bits = self.bytes.get(..usize::try_from(count.div_ceil(8)).ok()?)?;
+            self.bytes = &self.bytes[bits.len()..];
+            bits.iter().map(|b| u64::from(b.count_ones())).sum()
+        };
+        self.skip(defined.checked_mul(4)?)
+    }
+}
+
+// 解析编码的文件头 (kEncodedHeader 之后的 StreamsInfo)，返回压缩数据和各编码器输出的总大小
+// 输出大小包括中间编码器 (如 BCJ) 的输出，用于检查额度时偏大
+#[cfg(not(target_arch = "wasm32"))]
+fn encoded_header_sizes(header: &[u8]) -> Option<(u64, u64)> {
+    let mut reader = HeaderReader { bytes: header };
+    let (mut packed, mut unpacked) = (0u64, 0u64);
+    loop {
+        match reader.byte()? {
+            K_END => return Some((packed, unpacked)),
+            K_PACK_INFO => {
+                reader.number()?;
+                let streams = reader.number()?;
+                loop {
+                    match reader.byte()? {
+                        K_END => break,
+                        K_SIZE => {
+                            for _ in 0..streams {
+                                packed = packed.checked_add(reader.number()?)?;
+                            }
+                        }
+                        K_CRC => reader.skip_digests(streams)?,
+                        _ => return None,
+                    }
+                }
+            }
+            K_UNPACK_INFO => {
+                if reader.byte()? != K_FOLDER {
+                    return None;
+                }
+                let folders = reader.number()?;
+                // 文件头中的文件夹不会引用外部数据
+                if reader.byte()? != 0 {
+                    return None;
+                }
+                let mut outputs = 0u64;
+                for _ in 0..folders {
+                    let (mut folder_inputs, mut folder_outputs) = (0u64, 0u64);
+                    for _ in 0..reader.number()? {
+                        let flags = reader.byte()?;
+                        reader.skip(u64::from(flags & 0x0F))?;
+                        if flags & 0x10 != 0 {
+                            folder_inputs = folder_inputs.checked_add(reader.number()?)?;
+                            folder_outputs = folder_outputs.checked_add(reader.number()?)?;
+                        } else {
+                            folder_inputs += 1;
+                            folder_outputs += 1;
+                        }
+                        if flags & 0x20 != 0 {
+                            let properties = reader.number()?;
+                            reader.skip(properties)?;
+                        }
+                    }
+                    let bind_pairs = folder_outputs.checked_sub(1)?;
+                    for _ in 0..bind_pairs {
+                        reader.number()?;
+                        reader.number()?;
+                    }
+                    let packed_streams = folder_inputs.checked_sub(bind_pairs)?;
+                    if packed_streams > 1 {
+                        for _ in 0..packed_streams {
+                            reader.number()?;
+                        }
+                    }
+                    outputs = outputs.checked_add(folder_outputs)?;
+                }
+                if reader.byte()? != K_CODERS_UNPACK_SIZE {
+                    return None;
+                }
+                for _ in 0..outputs {
+                    unpacked = unpacked.checked_add(reader.number()?)?;
+                }
+                loop {
+                    match reader.byte()? {
+                        K_END => break,
+                        K_CRC => reader.skip_digests(folders)?,
+                        _ => return None,
+                    }
+                }
+            }
+            _ => return None,
+        }
+    }
+}
+
+// 从 zip 末尾的 EOCD (需要时再读 ZIP64 EOCD) 读取 (条目总数, 中央目录字节数)，找不到时返回 None
+fn zip_directory<R: Read + Seek>(reader: &mut R) -> Option<(u64, u64)> {
+    // EOCD 22 字节，后面最多跟 65535 字节的注释
+    const EOCD_SIZE: u64 = 22;
+    let len = reader.seek(SeekFrom::End(0)).ok()?;
+    let tail_len = len.min(EOCD_SIZE + u16::MAX as u64);
+    reader.seek(SeekFrom::Start(len - tail_len)).ok()?;
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.read_exact(&mut tail).ok()?;
+
+    let eocd = (0..=tail.len().checked_sub(EOCD_SIZE as usize)?).rev().find(|&i| tail[i..].starts_with(b"PK\x05\x06"))?;
+    let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]) as u64;
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes(b[i..i + 4].try_into().unwrap()) as u64;
+    let u64_at = |b: &[u8], i: usize| u64::from_le_bytes(b[i..i + 8].try_into().unwrap());
+    let entries = u16_at(&tail, eocd + 10);
+    let size = u32_at(&tail, eocd + 12);
+    if entries != u16::MAX as u64 && size != u32::MAX as u64 {
+        return Some((entries, size));
+    }
+
+    // ZIP64：EOCD 前 20 字节是 ZIP64 EOCD 定位器
+    let locator = eocd.checked_sub(20)?;
+    if !tail[locator..].starts_with(b"PK\x06\x07") {
+        return Some((entries, size));
+    }
+    reader.seek(SeekFrom::Start(u64_at(&tail, locator + 8))).ok()?;
+    let mut record = [0u8; 56];
+    reader.read_exact(&mut record).ok()?;
+    record.starts_with(b"PK\x06\x06").then(|| (u64_at(&record, 32), u64_at(&record, 40)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    use super::*;
+    use crate::utils::test_dir;
+
+    fn budget(max_depth: usize, entries: usize, bytes: u64) -> ArchiveBudget {
+        ArchiveBudget { max_depth, entries_left: Cell::new(entries), bytes_left: Cell::new(bytes), sniff_left: Cell::new(SNIFF_BYTES) }
+    }
+
+    fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, data) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn write(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn list(path: &Path, format: ArchiveFormat, budget: &ArchiveBudget) -> (Vec<String>, ArchiveListing) {
+        let mut paths = vec![];
+        let listing = list_archive(path, format, budget, None, |entry| paths.push(entry.path));
+        (paths, listing)
+    }
+
+    fn nested_zip() -> Vec<u8> {
+        let inner = zip_bytes(&[("deep/c.txt", b"inner text")]);
+        let package = tar_gz_bytes(&[("lib/b.rs", b"fn main() {}")]);
+        zip_bytes(&[("readme.txt", b"outer"), ("docs/", b""), ("inner.zip", &inner), ("pkg.tar.gz", &package)])
+    }
+
+    #[test]
+    fn nested_archives_are_listed_with_prefixes() {
+        let dir = test_dir("archive-nested");
+        let path = write(&dir, "outer.zip", &nested_zip());
+
+        let (paths, listing) = list(&path, ArchiveFormat::ZIP, &budget(3, 100, 1 << 20));
+        assert_eq!(
+            paths,
+            ["readme.txt", "inner.zip", "inner.zip!/deep/c.txt", "pkg.tar.gz", "pkg.tar.gz!/lib/b.rs"]
+        );
+        assert_eq!(listing.entries, 5);
+        assert_eq!(listing.limit, None);
+        assert_eq!(listing.error, None);
+
+        // 深度 1 只列出复制的压缩包本身的条目
+        let (paths, listing) = list(&path, ArchiveFormat::ZIP, &budget(1, 100, 1 << 20));
+        assert_eq!(paths, ["readme.txt", "inner.zip", "pkg.tar.gz"]);
+        assert_eq!(listing.limit, Some(ArchiveLimit::DEPTH));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn entry_budget_is_checked_before_parsing_the_zip_directory() {
+        let dir = test_dir("archive-entries");
+        let names: Vec<String> = (0..5).map(|i| format!("file{}.txt", i)).collect();
+        let entries: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), &b"x"[..])).collect();
+        let zip = write(&dir, "many.zip", &zip_bytes(&entries));
+        let tar_gz = write(&dir, "many.tar.gz", &tar_gz_bytes(&entries));
+
+        assert_eq!(zip_directory(&mut File::open(&zip).unwrap()).map(|(entries, _)| entries), Some(5));
+        let (paths, listing) = list(&zip, ArchiveFormat::ZIP, &budget(3, 3, 1 << 20));
+        assert!(paths.is_empty());
+        assert_eq!(listing.limit, Some(ArchiveLimit::ENTRIES));
+
+        // tar 没有目录，逐个条目计数
+        let (paths, listing) = list(&tar_gz, ArchiveFormat::TARGZ, &budget(3, 3, 1 << 20));
+        assert_eq!(paths.len(), 3);
+        assert_eq!(listing.limit, Some(ArchiveLimit::ENTRIES));
+
+        // 额度由一次复制中的全部压缩包共享
+        let shared = budget(3, 7, 1 << 20);
+        assert_eq!(list(&tar_gz, ArchiveFormat::TARGZ, &shared).0.len(), 5);
+        assert_eq!(list(&zip, ArchiveFormat::ZIP, &shared).1.limit, Some(ArchiveLimit::ENTRIES));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn byte_budget_limits_directories_and_decompression() {
+        let dir = test_dir("archive-bytes");
+        let zip = write(&dir, "small.zip", &zip_bytes(&[("a.txt", b"a")]));
+        let (_, directory_size) = zip_directory(&mut File::open(&zip).unwrap()).unwrap();
+        let (paths, listing) = list(&zip, ArchiveFormat::ZIP, &budget(3, 100, directory_size - 1));
+        assert!(paths.is_empty());
+        assert_eq!(listing.limit, Some(ArchiveLimit::BYTES));
+
+        // tar.gz 解压出的全部字节都计入额度
+        let large = vec![b'x'; 64 * 1024];
+        let tar_gz = write(&dir, "large.tar.gz", &tar_gz_bytes(&[("big.txt", &large), ("after.txt", b"y")]));
+        let (paths, listing) = list(&tar_gz, ArchiveFormat::TARGZ, &budget(3, 100, 32 * 1024));
+        assert_eq!(paths, ["big.txt"]);
+        assert_eq!(listing.limit, Some(ArchiveLimit::BYTES));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn listing_stops_at_the_walk_deadline() {
+        let dir = test_dir("archive-deadline");
+        let path = write(&dir, "outer.zip", &nested_zip());
+        let mut paths = vec![];
+        let listing = list_archive(&path, ArchiveFormat::ZIP, &budget(3, 100, 1 << 20), Some(Instant::now()), |entry| {
+            paths.push(entry.path)
+        });
+        assert!(paths.is_empty());
+        assert_eq!(listing.limit, Some(ArchiveLimit::TIME));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn sniffing_entries_is_not_charged_to_the_byte_budget() {
+        let dir = test_dir("archive-sniff");
+        let names: Vec<String> = (0..40).map(|i| format!("sheet{}.txt", i)).collect();
+        let content = vec![b'x'; 2 * HEADER_BYTES];
+        let entries: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), content.as_slice())).collect();
+        let zip = write(&dir, "many.zip", &zip_bytes(&entries));
+        let (_, directory_size) = zip_directory(&mut File::open(&zip).unwrap()).unwrap();
+
+        // 识别每个条目开头读取的 16 KiB 不计入解压额度
+        let budget = budget(3, 100, directory_size);
+        let (paths, listing) = list(&zip, ArchiveFormat::ZIP, &budget);
+        assert_eq!(paths.len(), 40);
+        assert_eq!(listing.limit, None);
+        assert_eq!(budget.bytes_left.get(), 0);
+
+        // 识别额度用完后仍列出名称，只是不识别内容
+        let small = self::budget(3, 100, 1 << 20);
+        small.sniff_left.set(HEADER_BYTES as u64 * 3);
+        let mut contents = vec![];
+        let listing = list_archive(&zip, ArchiveFormat::ZIP, &small, None, |entry| contents.push(entry.content));
+        assert_eq!(contents.len(), 40);
+        assert_eq!(listing.limit, None);
+        assert_eq!(small.sniff_left.get(), 0);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn oversized_encoded_7z_header_is_not_decoded() {
+        let dir = test_dir("archive-7z-encoded");
+        // 7z 变长整数的 8 字节形式
+        let number = |value: u64| [&[0xFF][..], &value.to_le_bytes()].concat();
+        let mut header = vec![K_ENCODED_HEADER, K_PACK_INFO];
+        header.extend(number(0));
+        header.extend(number(1));
+        header.push(K_SIZE);
+        header.extend(number(16));
+        header.extend([K_END, K_UNPACK_INFO, K_FOLDER]);
+        header.extend(number(1));
+        header.push(0);
+        // 一个 LZMA 编码器
+        header.extend(number(1));
+        header.extend([0x03, 0x03, 0x01, 0x01]);
+        header.push(K_CODERS_UNPACK_SIZE);
+        header.extend(number(1 << 40));
+        header.extend([K_END, K_END]);
+        assert_eq!(encoded_header_sizes(&header[1..]), Some((16, 1 << 40)));
+
+        let mut bytes = b"7z\xBC\xAF\x27\x1C\x00\x04".to_vec();
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&header);
+        let path = write(&dir, "encoded.7z", &bytes);
+        let (paths, listing) = list(&path, ArchiveFormat::SEVENZIP, &budget(3, 100, 1 << 20));
+        assert!(paths.is_empty());
+        assert_eq!(listing.limit, Some(ArchiveLimit::BYTES));
+
+        // 解析不了的编码文件头不交给解码器
+        let truncated = &bytes[..bytes.len() - 4];
+        let mut bytes = truncated.to_vec();
+        bytes[20..28].copy_from_slice(&(header.len() as u64 - 4).to_le_bytes());
+        let path = write(&dir, "truncated.7z", &bytes);
+        let (_, listing) = list(&path, ArchiveFormat::SEVENZIP, &budget(3, 100, 1 << 20));
+        assert_eq!(listing.error.as_deref(), Some("invalid 7z encoded header"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn oversized_7z_header_is_not_parsed() {
+        let dir = test_dir("archive-7z");
+        let mut header = b"7z\xBC\xAF\x27\x1C\x00\x04".to_vec();
+        header.extend_from_slice(&[0; 4]);
+        header.extend_from_slice(&0u64.to_le_bytes());
+        header.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        header.extend_from_slice(&[0; 4]);
+        let path = write(&dir, "huge.7z", &header);
+        let (paths, listing) = list(&path, ArchiveFormat::SEVENZIP, &budget(3, 100, 1 << 20));
+        assert!(paths.is_empty());
+        assert_eq!(listing.limit, Some(ArchiveLimit::BYTES));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
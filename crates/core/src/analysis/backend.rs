@@ -0,0 +1,157 @@
+// 剪贴板后端抽象：分类逻辑只依赖这个 trait，不直接调用任何平台 API
+// 格式统一用字符串名称表示：Windows 标准格式使用 "CF_xxx"，注册格式使用其注册名，
+// X11 / Wayland 直接使用 MIME 类型 (TARGETS 中的 atom 名称)
+
+use crate::error::MonitorError;
+use crate::global::Selection;
+
+pub const FORMAT_HDROP: &str = "CF_HDROP";
+pub const FORMAT_BITMAP: &str = "CF_BITMAP";
+pub const FORMAT_DIB: &str = "CF_DIB";
+pub const FORMAT_UNICODETEXT: &str = "CF_UNICODETEXT";
+pub const FORMAT_HTML: &str = "HTML Format";
+pub const FORMAT_CSV: &str = "Csv";
+pub const FORMAT_URI_LIST: &str = "text/uri-list";
+pub const FORMAT_GNOME_FILES: &str = "x-special/gnome-copied-files";
+pub const FORMAT_PNG: &str = "image/png";
+pub const FORMAT_BMP: &str = "image/bmp";
+pub const FORMAT_JPEG: &str = "image/jpeg";
+pub const FORMAT_HTML_MIME: &str = "text/html";
+pub const FORMAT_CSV_MIME: &str = "text/csv";
+pub const FORMAT_TEXT_MIME: &str = "text/plain;charset=utf-8";
+pub const FORMAT_UTF8_STRING: &str = "UTF8_STRING";
+pub const FORMAT_PLAIN_MIME: &str = "text/plain";
+
+// X11 / Wayland 单次读取的数据上限，防止超大内容占满内存
+pub const MAX_TRANSFER_BYTES: usize = 64 * 1024 * 1024;
+
+// DROPFILES 结构体大小: pFiles(4) + pt(8) + fNC(4) + fWide(4)
+const DROPFILES_SIZE: usize = 20;
+
+pub trait ClipboardBackend {
+    // 列出当前剪贴板上的全部格式，剪贴板为空时返回空列表
+    // 暂时无法读取 (如被其他程序占用) 时返回 None，调用方应跳过这次变化而不是当作剪贴板被清空
+    fn formats(&mut self) -> Option<Vec<String>>;
+
+    // 读取指定格式的原始字节，格式不存在或读取失败时返回 None
+    fn read(&mut self, format: &str) -> Option<Vec<u8>>;
+
+    // 获取剪贴板所有者进程 (pid, 进程名)
+    fn owner(&mut self) -> Option<(u32, String)>;
+
+    // 当前变化发生的时间 (Unix 毫秒)，None 表示就是现在；回放录制时为录制下来的时间
+    fn changed_at(&self) -> Option<u64> {
+        None
+    }
+
+    // 平台能否提供所有者进程，不能时 owner 返回 None 是正常情况
+    fn knows_owner(&self) -> bool {
+        true
+    }
+
+    // 阻塞等待下一次剪贴板变化，返回 false 表示监听已结束
+    fn wait_for_change(&mut self) -> bool;
+
+    // wait_for_change 返回 false 后取出导致结束的错误，正常停止时为 None
+    fn take_error(&mut self) -> Option<MonitorError> {
+        None
+    }
+
+    // 当前这次变化来自哪个选区，只支持 CLIPBOARD 的后端无需覆盖
+    fn selection(&self) -> Selection {
+        Selection::CLIPBOARD
+    }
+
+    // 判断格式是否存在，平台后端可以覆盖为更廉价的实现
+    fn has_format(&mut self, format: &str) -> bool {
+        self.formats().is_some_and(|formats| formats.iter().any(|f| f == format))
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let raw = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]))
+}
+
+// 解析 CF_HDROP 的 DROPFILES 数据，返回其中的文件路径列表
+pub fn parse_drop_files(bytes: &[u8]) -> Vec<String> {
+    let (Some(offset), Some(wide)) = (read_u32(bytes, 0), read_u32(bytes, 16)) else {
+        return vec![];
+    };
+    let Some(list) = bytes.get(offset as usize..) else {
+        return vec![];
+    };
+
+    if wide != 0 {
+        let chars: Vec<u16> = list
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        chars
+            .split(|&c| c == 0)
+            .take_while(|s| !s.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect()
+    } else {
+        list.split(|&c| c == 0)
+            .take_while(|s| !s.is_empty())
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect()
+    }
+}
+
+// 将路径列表编码为 CF_HDROP 的 DROPFILES 数据 (宽字符)，供 Mock 等后端构造剪贴板内容
+pub fn encode_drop_files<S: AsRef<str>>(paths: &[S]) -> Vec<u8> {
+    let mut bytes = vec![0u8; DROPFILES_SIZE];
+    bytes[0..4].copy_from_slice(&(DROPFILES_SIZE as u32).to_le_bytes());
+    bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+
+    for path in paths {
+        for c in path.as_ref().encode_utf16().chain(std::iter::once(0)) {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+    }
+    // 列表以双 null 结尾
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                decoded.push((hi << 4) | lo);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// 解析 text/uri-list 和 x-special/gnome-copied-files，只保留本地 file:// 路径
+pub fn parse_uri_list(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.strip_prefix("file://"))
+        // file://host/path 形式，跳过主机名
+        .filter_map(|rest| rest.find('/').map(|slash| &rest[slash..]))
+        .map(percent_decode)
+        .collect()
+}
@@ -0,0 +1,379 @@
+// 代码片段识别：按关键字和符号的启发式规则判断复制的文本是否是源代码，并猜测编程语言
+// 每种语言有一组带权重的正则，命中次数 (每条最多计 MAX_HITS 次) 乘权重累加为得分，取得分最高的语言
+// 关键字容易出现在普通文字中的语言 (如 Shell) 另有一组锚点规则，没有命中锚点时不计分
+// 只是粗略的猜测，置信度综合了得分高低和与第二名的差距
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    RUST,
+    C,
+    CPP,
+    CSHARP,
+    JAVA,
+    GO,
+    PYTHON,
+    JAVASCRIPT,
+    TYPESCRIPT,
+    SQL,
+    SHELL,
+}
+
+// 识别结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeGuess {
+    pub language: CodeLanguage,
+    // 0 ~ 1，保留两位小数
+    pub confidence: f64,
+}
+
+// 只分析开头这么多字节，避免复制大段文本时拖慢监听线程
+const MAX_SCAN_BYTES: usize = 64 * 1024;
+// 每条规则最多计入的命中次数，避免一种写法重复很多次时得分失真
+const MAX_HITS: usize = 3;
+// 得分低于这个值不算代码
+const MIN_SCORE: usize = 4;
+// 符号 (括号、分号、等号等) 占非空白字符的比例低于这个值时，得分还需达到 STRONG_SCORE 才算代码
+const MIN_SYMBOL_DENSITY: f64 = 0.03;
+const STRONG_SCORE: usize = 8;
+// 得分为这个值时强度为 0.5
+const HALF_SCORE: f64 = 6.0;
+
+struct LanguageRules {
+    language: CodeLanguage,
+    // 规则只包含与 base 不同的写法，有命中时再加上 base 的得分 (如 TypeScript 之于 JavaScript)
+    base: Option<CodeLanguage>,
+    rules: Vec<(Regex, usize)>,
+    // (规则, 最少命中次数)，不为空时至少满足一条才计分
+    anchors: Vec<(Regex, usize)>,
+}
+
+impl LanguageRules {
+    fn anchored(mut self, anchors: &[(&str, usize)]) -> Self {
+        self.anchors = compile(anchors);
+        self
+    }
+
+    fn score(&self, text: &str) -> usize {
+        let anchored = self.anchors.is_empty()
+            || self.anchors.iter().any(|(regex, min_hits)| regex.find_iter(text).take(*min_hits).count() == *min_hits);
+        if !anchored {
+            return 0;
+        }
+        self.rules
+            .iter()
+            .map(|(regex, weight)| regex.find_iter(text).take(MAX_HITS).count() * weight)
+            .sum()
+    }
+}
+
+fn compile(rules: &[(&str, usize)]) -> Vec<(Regex, usize)> {
+    rules
+        .iter()
+        .map(|&(expr, weight)| (Regex::new(&format!("(?m){}", expr)).expect("invalid code rule"), weight))
+        .collect()
+}
+
+fn language(language: CodeLanguage, base: Option<CodeLanguage>, rules: &[(&str, usize)]) -> LanguageRules {
+    LanguageRules { language, base, rules: compile(rules), anchors: vec![] }
+}
+
+// Shell 中同时用作计分和锚点的规则
+const SHELL_SHEBANG: &str = r"^#!\s*/(usr/)?bin/(env\s+)?(ba|z|k)?sh\b";
+const SHELL_PROMPT: &str = r"^\s*\$\s+\w";
+const SHELL_PIPE: &str = r"\|\s*(grep|awk|sed|xargs|sort|uniq|head|tail|wc|tee|jq)\b";
+const SHELL_CONTROL: &str = r"^\s*(fi|done|esac)\s*$|;\s*then\b|;\s*do\b";
+// 行首的常见命令
+const SHELL_COMMAND: &str = r"^\s*(sudo|apt|apt-get|yum|dnf|brew|npm|npx|pnpm|yarn|pip|pip3|cargo|cd|ls|mkdir|rm|cp|mv|chmod|chown|export|echo|grep|curl|wget|git|docker|kubectl|make|tar|ssh)";
+
+static LANGUAGES: LazyLock<Vec<LanguageRules>> = LazyLock::new(|| {
+    use CodeLanguage::*;
+    vec![
+        language(RUST, None, &[
+            (r"\bfn\s+\w+\s*(<[^>]*>)?\s*\(", 3),
+            (r"\blet\s+mut\b", 3),
+            (r"\bimpl\b(<[^>]*>)?\s+[\w:]+", 3),
+            (r"^\s*use\s+\w+(::[\w{}*, ]+)+;", 3),
+            (r"#!?\[(derive|cfg|test|allow|deny)\b", 3),
+            (r"\b(println|print|format|vec|write|writeln|panic|assert|assert_eq|macro_rules)!", 3),
+            (r"\b(Some|Ok|Err)\(|\bNone\b", 1),
+            (r"\.unwrap\(\)|\?;", 2),
+            (r"&mut\s|&'\w+\b|&self\b", 2),
+            (r"\bmatch\s+[^{]+\{", 2),
+            (r"^\s*(pub(\([\w ]+\))?\s+)?(struct|enum|trait|mod)\s+\w+", 2),
+            (r"->\s*(Self|Option|Result|Vec|bool|String|&?str|[iu](8|16|32|64|size))\b", 2),
+        ]),
+        language(C, None, &[
+            (r"^\s*#include\s*<\w+(/\w+)*\.h>", 3),
+            (r"^\s*#\s*(define|ifdef|ifndef|endif|pragma)\b", 2),
+            (r"\b(printf|fprintf|sprintf|scanf|malloc|calloc|free|memcpy|strlen)\s*\(", 2),
+            (r"^\s*(static\s+)?(unsigned\s+)?(int|void|char|long|double|float)\s+\**\w+\s*\(", 2),
+            (r"\btypedef\s+(struct|enum|union)\b", 3),
+            (r"\b\w+->\w+", 1),
+            (r"\bNULL\b", 1),
+        ]),
+        language(CPP, Some(C), &[
+            (r"^\s*#include\s*<\w+>", 3),
+            (r"\bstd::\w+", 3),
+            (r"\b(cout|cerr)\s*<<|\bcin\s*>>", 3),
+            (r"\btemplate\s*<", 3),
+            (r"^\s*(public|private|protected):", 3),
+            (r"\bnullptr\b", 3),
+            (r"^\s*namespace\s+\w+|\busing\s+namespace\b", 2),
+            (r"\bvirtual\b|\boverride\b|\bconstexpr\b", 2),
+            (r"\bauto\s+&?\w+\s*[=:]", 2),
+        ]),
+        language(CSHARP, None, &[
+            (r"^\s*using\s+System(\.\w+)*;", 4),
+            (r"\bConsole\.Write(Line)?\(", 4),
+            (r"\{\s*get;\s*(private\s+)?(set;)?\s*\}", 4),
+            (r"\basync\s+Task\b|\bTask<", 3),
+            (r"^\s*namespace\s+[\w.]+", 1),
+            (r"\b(public|private|internal)\s+(static\s+|override\s+|readonly\s+)*(void|string|int|bool|var)\s+\w+", 2),
+            (r"\bstring\[\]|\bvar\s+\w+\s*=\s*new\b", 2),
+        ]),
+        language(JAVA, None, &[
+            (r"^\s*package\s+[\w.]+;", 4),
+            (r"^\s*import\s+(static\s+)?java(x)?\.", 4),
+            (r"\bSystem\.(out|err)\.print", 4),
+            (r"@Override\b", 4),
+            (r"\bpublic\s+static\s+void\s+main\s*\(\s*String", 4),
+            (r"\b(public|private|protected)\s+(final\s+)?(class|interface)\s+\w+", 2),
+            (r"\b(extends|implements)\s+\w+", 2),
+            (r"\bthrows\s+\w+", 3),
+            (r"\b(private|protected)\s+(static\s+)?(final\s+)?[\w<>\[\]]+\s+\w+\s*[;=]", 2),
+            (r"\bString\s+\w+\s*=", 1),
+        ]),
+        language(GO, None, &[
+            (r"^\s*package\s+\w+\s*$", 4),
+            (r"\bfunc\s+(\(\w+\s+\*?\w+\)\s*)?\w+\s*\(", 4),
+            (r"\bfmt\.\w+\(", 4),
+            (r"\berr\s*!=\s*nil\b", 4),
+            (r"^\s*import\s*\(", 3),
+            (r"\w\s*:=", 2),
+            (r"\bdefer\s", 3),
+            (r"\bgo\s+func\b|\bchan\s+\w+|<-\s*\w+", 2),
+            (r"\bmap\[\w+\]", 3),
+            (r"\btype\s+\w+\s+(struct|interface)\s*\{", 3),
+        ]),
+        language(PYTHON, None, &[
+            (r"^\s*def\s+\w+\s*\(.*\)\s*(->\s*[^:]+)?:\s*$", 4),
+            (r"^\s*class\s+\w+(\(.*\))?:\s*$", 3),
+            (r"^\s*(from\s+[\w.]+\s+)?import\s+[\w.]+(\s+as\s+\w+)?\s*$", 2),
+            (r"\bself\.\w+", 3),
+            (r"^\s*(if|elif|while|for|with|try|except|else)\b.*:\s*$", 2),
+            (r"\belif\b", 4),
+            (r"__\w+__", 3),
+            (r"\blambda\s+\w*:", 3),
+            (r"\bin\s+range\(", 3),
+            (r"\b(None|True|False)\b", 1),
+            (r"\bprint\(", 1),
+        ]),
+        language(JAVASCRIPT, None, &[
+            (r"\b(const|let)\s+\w+\s*=", 2),
+            (r"\bfunction\s*\w*\s*\(", 2),
+            (r"=>\s*[{(\w]", 1),
+            (r"\bconsole\.(log|error|warn)\(", 4),
+            (r"\b(document|window)\.\w+", 3),
+            (r"\brequire\(['\x22]", 3),
+            (r"\bmodule\.exports\b|^\s*export\s+(default|const|function|class)\b", 3),
+            (r"^\s*import\s+.+\s+from\s+['\x22]", 3),
+            (r"===|!==", 3),
+            (r"\bundefined\b", 2),
+            (r"\bawait\s|\basync\s+(function|\()", 1),
+            (r"\$\{", 1),
+        ]),
+        language(TYPESCRIPT, Some(JAVASCRIPT), &[
+            (r"\w\??:\s*(string|number|boolean|any|unknown|void|never)\b", 4),
+            (r"^\s*(export\s+)?interface\s+\w+\s*(<[^>]*>)?\s*\{", 3),
+            (r"^\s*(export\s+)?type\s+\w+\s*(<[^>]*>)?\s*=", 3),
+            (r"\b(public|private|readonly)\s+\w+\s*:", 2),
+            (r"\bas\s+(string|number|any|unknown|const)\b", 2),
+        ]),
+        language(SQL, None, &[
+            (r"\bSELECT\s+.+\s+FROM\s+\w+", 5),
+            (r"(?i)\bselect\s+(\*|distinct\b|count\(|[\w.]+(\s*,\s*[\w.]+)*\s+from\b)", 4),
+            (r"(?i)\binsert\s+into\s+\w+", 5),
+            (r"(?i)\bupdate\s+\w+\s+set\s+\w+\s*=", 5),
+            (r"(?i)\bdelete\s+from\s+\w+", 5),
+            (r"(?i)\b(create|alter|drop)\s+(table|index|view|database)\b", 5),
+            (r"\b(WHERE|GROUP BY|ORDER BY|HAVING|LIMIT|VALUES)\b", 2),
+            (r"\b((LEFT|RIGHT|INNER|OUTER|CROSS)\s+)?JOIN\s+\w+", 2),
+        ]),
+        // 命令名和 && 在普通文字中也很常见 ("make sure you cd into it && run")，
+        // 需要有 shebang、提示符、管道、带选项的命令、控制结构，或者至少两行以命令开头
+        language(SHELL, None, &[
+            (SHELL_SHEBANG, 5),
+            (SHELL_PROMPT, 3),
+            (&format!(r"{}\s", SHELL_COMMAND), 3),
+            (SHELL_PIPE, 3),
+            (SHELL_CONTROL, 3),
+            (r"\$\(\w|\$\{?\w+\}?", 1),
+            (r"\s--?[a-zA-Z][\w-]*", 1),
+            (r"&&|\|\|", 1),
+        ])
+        .anchored(&[
+            (SHELL_SHEBANG, 1),
+            (SHELL_PROMPT, 1),
+            (SHELL_PIPE, 1),
+            (&format!(r"{}\s[^\n]*\s--?[a-zA-Z]", SHELL_COMMAND), 1),
+            (SHELL_CONTROL, 1),
+            (&format!(r"{}(\s[^\n]*)?$", SHELL_COMMAND), 2),
+        ]),
+    ]
+});
+
+// 判断文本是否是代码，是时返回最可能的语言
+pub fn detect_language(text: &str) -> Option<CodeGuess> {
+    let text = truncate(text, MAX_SCAN_BYTES);
+    if looks_like_prose(text) {
+        return None;
+    }
+
+    let raw: Vec<(CodeLanguage, usize)> = LANGUAGES
+        .iter()
+        .map(|rules| (rules.language, rules.score(text)))
+        .collect();
+    let score_of = |language: CodeLanguage| raw.iter().find(|(l, _)| *l == language).map_or(0, |(_, s)| *s);
+    let scores: Vec<(CodeLanguage, usize)> = LANGUAGES
+        .iter()
+        .zip(&raw)
+        .map(|(rules, &(language, score))| match rules.base {
+            Some(base) if score > 0 => (language, score + score_of(base)),
+            _ => (language, score),
+        })
+        .collect();
+
+    let &(language, best) = scores.iter().max_by_key(|(_, score)| *score)?;
+    if best < MIN_SCORE || (symbol_density(text) < MIN_SYMBOL_DENSITY && best < STRONG_SCORE) {
+        return None;
+    }
+    // 第二名不算获胜语言的 base 和以获胜语言为 base 的语言，它们的得分本来就互相包含
+    let related = |other: CodeLanguage| {
+        LANGUAGES.iter().any(|rules| {
+            (rules.language == language && rules.base == Some(other))
+                || (rules.language == other && rules.base == Some(language))
+        })
+    };
+    let second = scores
+        .iter()
+        .filter(|&&(other, _)| other != language && !related(other))
+        .map(|&(_, score)| score)
+        .max()
+        .unwrap_or(0);
+
+    let best = best as f64;
+    let strength = best / (best + HALF_SCORE);
+    let margin = (best - second as f64).max(0.0) / best;
+    let confidence = strength * (0.5 + 0.5 * margin);
+    Some(CodeGuess { language, confidence: (confidence * 100.0).round() / 100.0 })
+}
+
+fn truncate(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+// 超过一半的非空行是以句末标点结尾的长句时，按普通文字处理
+fn looks_like_prose(text: &str) -> bool {
+    let mut lines = 0;
+    let mut prose = 0;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        lines += 1;
+        let words = line.split_whitespace().count();
+        if words >= 8 && line.ends_with(['.', '!', '?', '。', '！', '？']) {
+            prose += 1;
+        }
+    }
+    prose * 2 > lines
+}
+
+// 代码中常见的符号占非空白字符的比例
+fn symbol_density(text: &str) -> f64 {
+    let mut total = 0usize;
+    let mut symbols = 0usize;
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        total += 1;
+        if matches!(c, '{' | '}' | '(' | ')' | '[' | ']' | ';' | '=' | '<' | '>' | '|' | '&' | '$' | '*' | ':') {
+            symbols += 1;
+        }
+    }
+    if total == 0 {
+        0.0
+    } else {
+        symbols as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_language_is_detected() {
+        use CodeLanguage::*;
+        let samples = [
+            (RUST, "use std::io;\n\nfn main() {\n    let mut line = String::new();\n    io::stdin().read_line(&mut line).unwrap();\n    println!(\"{}\", line);\n}\n"),
+            (C, "#include <stdio.h>\n\nint main(void) {\n    char *buf = malloc(16);\n    printf(\"%s\\n\", buf);\n    free(buf);\n    return 0;\n}\n"),
+            (CPP, "#include <vector>\n\nint main() {\n    std::vector<int> items{1, 2, 3};\n    for (auto &item : items) {\n        std::cout << item << std::endl;\n    }\n}\n"),
+            (CSHARP, "using System;\n\npublic class Person {\n    public string Name { get; set; }\n    public void Greet() {\n        Console.WriteLine(\"Hello \" + Name);\n    }\n}\n"),
+            (JAVA, "package com.example;\n\nimport java.util.List;\n\npublic class App {\n    public static void main(String[] args) {\n        System.out.println(\"hello\");\n    }\n}\n"),
+            (GO, "package main\n\nimport (\n    \"fmt\"\n)\n\nfunc main() {\n    value, err := run()\n    if err != nil {\n        return\n    }\n    fmt.Println(value)\n}\n"),
+            (PYTHON, "import os\n\nclass Loader:\n    def __init__(self, path):\n        self.path = path\n\n    def load(self):\n        for name in os.listdir(self.path):\n            print(name)\n"),
+            (JAVASCRIPT, "const fs = require('fs');\n\nfunction read(path) {\n  const text = fs.readFileSync(path, 'utf8');\n  console.log(text === undefined);\n  return text;\n}\n"),
+            (TYPESCRIPT, "export interface User {\n  id: number;\n  name: string;\n}\n\nexport const greet = (user: User): string => {\n  return `hi ${user.name}`;\n};\n"),
+            (SQL, "SELECT id, name FROM users\nWHERE active = 1\nORDER BY name;\n"),
+            (SHELL, "#!/bin/bash\nset -e\nfor file in *.log; do\n  grep -c ERROR \"$file\" | tee -a counts.txt\ndone\n"),
+        ];
+        for (language, text) in samples {
+            let guess = detect_language(text).unwrap_or_else(|| panic!("{:?} sample not detected", language));
+            assert_eq!(guess.language, language, "sample:\n{}", text);
+            assert!(guess.confidence > 0.0 && guess.confidence <= 1.0);
+        }
+    }
+
+    #[test]
+    fn shell_needs_command_structure() {
+        let samples = [
+            "$ cargo build --release",
+            "git clone --depth 1 https://example.com/repo.git && cd repo",
+            "cd project\nmake\nmake install\n",
+            "ps aux | grep node | head -n 5",
+        ];
+        for text in samples {
+            assert_eq!(detect_language(text).map(|guess| guess.language), Some(CodeLanguage::SHELL), "{}", text);
+        }
+    }
+
+    #[test]
+    fn prose_is_not_code() {
+        let samples = [
+            "make sure you cd into the folder && run it",
+            "Let me know if the meeting time works for you.",
+            "The function of this committee is to review the budget; please send comments by Friday.",
+            "cd into the folder, then open the file and check the results",
+            "We need to select the best candidates from the list and update the schedule for next week.",
+            "Shopping list: eggs, milk (2 litres), bread & butter",
+            "",
+        ];
+        for text in samples {
+            assert_eq!(detect_language(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn long_text_is_truncated_on_a_char_boundary() {
+        let text = "é".repeat(MAX_SCAN_BYTES);
+        assert_eq!(truncate(&text, MAX_SCAN_BYTES + 1).len(), MAX_SCAN_BYTES);
+        assert_eq!(truncate("short", MAX_SCAN_BYTES), "short");
+    }
+}
@@ -0,0 +1,87 @@
+// 事件过滤：每个订阅者只接收关心的事件类型、文件分类和来源进程
+
+use super::{ClipboardEvent, ClipboardReport};
+use crate::global::{FilesInfo, HashesInfo};
+
+// 事件类型，与 ClipboardReport 的分支一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    FILES,
+    IMAGE,
+    TABLE,
+    TEXT,
+    CLEARED,
+    CODE,
+    HASHES,
+}
+
+impl ClipboardReport {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            ClipboardReport::Files(_) => EventKind::FILES,
+            ClipboardReport::Shot(_) => EventKind::IMAGE,
+            ClipboardReport::Table(_) => EventKind::TABLE,
+            ClipboardReport::Text(_) => EventKind::TEXT,
+            ClipboardReport::Code(_) => EventKind::CODE,
+            ClipboardReport::Hashes(_) => EventKind::HASHES,
+            ClipboardReport::Cleared => EventKind::CLEARED,
+        }
+    }
+}
+
+// 过滤条件，空列表表示不限制；多个条件同时满足才匹配
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub kinds: Vec<EventKind>,
+    // 只对文件和哈希事件生效：只保留这些分类 (名称) 的文件，一个都不剩时丢弃事件
+    // 设置后其他事件一律不匹配
+    pub categories: Vec<String>,
+    // 来源进程名，不区分大小写 (如 "explorer.exe"、"nautilus")
+    pub pnames: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty() && self.categories.is_empty() && self.pnames.is_empty()
+    }
+
+    // 匹配时返回过滤后的事件 (文件列表可能被缩减)，不匹配返回 None
+    pub fn apply(&self, event: &ClipboardEvent) -> Option<ClipboardEvent> {
+        if !self.kinds.is_empty() && !self.kinds.contains(&event.report.kind()) {
+            return None;
+        }
+        if !self.pnames.is_empty() && !self.pnames.iter().any(|p| p.eq_ignore_ascii_case(&event.pname)) {
+            return None;
+        }
+        if self.categories.is_empty() {
+            return Some(event.clone());
+        }
+
+        let report = match &event.report {
+            ClipboardReport::Files(report) => report,
+            ClipboardReport::Hashes(hashes) => {
+                let files: Vec<_> = hashes.files.iter().filter(|f| self.categories.contains(&f.category)).cloned().collect();
+                if files.is_empty() {
+                    return None;
+                }
+                let hashes = HashesInfo { event_seq: hashes.event_seq, files };
+                return Some(ClipboardEvent { report: ClipboardReport::Hashes(hashes), ..event.clone() });
+            }
+            _ => return None,
+        };
+        let files: Vec<_> = report.files.iter().filter(|f| self.categories.contains(&f.category)).cloned().collect();
+        if files.is_empty() {
+            return None;
+        }
+        // 文件夹和压缩包的统计也只保留这些分类
+        let directories = report.directories.iter().cloned().map(|mut directory| {
+            directory.categories.retain(|category, _| self.categories.contains(category));
+            directory
+        }).collect();
+        let archives = report.archives.iter().cloned().map(|mut archive| {
+            archive.categories.retain(|category, _| self.categories.contains(category));
+            archive
+        }).collect();
+        Some(ClipboardEvent { report: ClipboardReport::Files(FilesInfo { files, directories, archives }), ..event.clone() })
+    }
+}
@@ -0,0 +1,170 @@
+// 宿主驱动的剪贴板后端：没有系统剪贴板 API 的环境 (如浏览器中的 WASI 构建)
+// 由宿主监听 copy / paste 等事件，把剪贴板内容作为快照推送进来，分析和上报逻辑与原生平台相同
+
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Mutex;
+
+use super::backend::ClipboardBackend;
+use super::mock::MockSnapshot;
+use crate::error::MonitorError;
+use crate::global::Selection;
+
+// 未被分析的快照最多排队数量，超出时丢弃新推送的快照
+const HOST_QUEUE_SIZE: usize = 64;
+
+// 当前运行中的宿主后端的推送入口
+static HOST_SENDER: Mutex<Option<SyncSender<MockSnapshot>>> = Mutex::new(None);
+
+pub struct HostBackend {
+    receiver: Receiver<MockSnapshot>,
+    current: MockSnapshot,
+}
+
+impl HostBackend {
+    // 创建后端并注册为推送目标，之前注册的宿主后端会随之结束
+    pub fn new() -> Self {
+        let (sender, receiver) = sync_channel(HOST_QUEUE_SIZE);
+        *HOST_SENDER.lock().unwrap() = Some(sender);
+        Self {
+            receiver,
+            current: MockSnapshot::default(),
+        }
+    }
+}
+
+impl Default for HostBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 宿主推送一次剪贴板内容
+pub fn push_snapshot(snapshot: MockSnapshot) -> Result<(), MonitorError> {
+    let guard = HOST_SENDER.lock().unwrap();
+    let Some(sender) = guard.as_ref() else {
+        return Err(MonitorError::new("Host clipboard backend is not running"));
+    };
+    sender.try_send(snapshot).map_err(|e| match e {
+        TrySendError::Full(_) => MonitorError::new("Host clipboard queue is full, snapshot dropped"),
+        TrySendError::Disconnected(_) => MonitorError::new("Host clipboard backend has stopped"),
+    })
+}
+
+// 注销推送入口，正在等待的宿主后端随之结束监听
+pub fn close_host() {
+    HOST_SENDER.lock().unwrap().take();
+}
+
+impl ClipboardBackend for HostBackend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        Some(self.current.format_names())
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        self.current.read(format)
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        self.current.owner.clone()
+    }
+
+    // 宿主 (如浏览器) 通常拿不到来源进程，快照中没有所有者是正常情况
+    fn knows_owner(&self) -> bool {
+        false
+    }
+
+    fn selection(&self) -> Selection {
+        self.current.selection
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        match self.receiver.recv() {
+            Ok(next) => {
+                self.current = next;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::analysis::{analyze_clipboard, ClipboardReport};
+    use crate::utils::lock_globals;
+
+    fn text(content: &str) -> MockSnapshot {
+        MockSnapshot::new().with_format("text/plain", content)
+    }
+
+    #[test]
+    fn pushed_snapshots_are_analyzed() {
+        let _globals = lock_globals();
+        let mut backend = HostBackend::new();
+        push_snapshot(text("copied in the browser")).unwrap();
+        push_snapshot(text("with owner").with_owner(7, "firefox")).unwrap();
+
+        assert!(backend.wait_for_change());
+        assert!(!backend.knows_owner());
+        let event = analyze_clipboard(&mut backend).expect("text event");
+        let ClipboardReport::Text(info) = &event.report else { unreachable!() };
+        assert_eq!(info.preview, "copied in the browser");
+        assert_eq!(event.pid, 0);
+
+        assert!(backend.wait_for_change());
+        let event = analyze_clipboard(&mut backend).expect("text event");
+        assert_eq!((event.pid, event.pname.as_str()), (7, "firefox"));
+        close_host();
+    }
+
+    #[test]
+    fn close_host_ends_the_wait() {
+        let _globals = lock_globals();
+        let mut backend = HostBackend::new();
+        let closer = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            close_host();
+        });
+        assert!(!backend.wait_for_change());
+        closer.join().unwrap();
+        assert!(push_snapshot(text("late")).unwrap_err().message.contains("not running"));
+
+        // 新的宿主后端替换旧的，旧后端取完已排队的快照后结束
+        let mut old = HostBackend::new();
+        push_snapshot(text("queued")).unwrap();
+        let mut current = HostBackend::new();
+        assert!(old.wait_for_change());
+        assert!(!old.wait_for_change());
+        push_snapshot(text("current")).unwrap();
+        assert!(current.wait_for_change());
+        assert_eq!(current.read("text/plain").as_deref(), Some(&b"current"[..]));
+        close_host();
+    }
+
+    #[test]
+    fn full_queue_drops_new_snapshots() {
+        let _globals = lock_globals();
+        let mut backend = HostBackend::new();
+        for index in 0..HOST_QUEUE_SIZE {
+            push_snapshot(text(&index.to_string())).unwrap();
+        }
+        let error = push_snapshot(text("overflow")).unwrap_err();
+        assert!(error.message.contains("full"), "{}", error);
+
+        // 取走一个后又可以推送，排队的快照按顺序交给监听
+        assert!(backend.wait_for_change());
+        assert_eq!(backend.read("text/plain").as_deref(), Some(&b"0"[..]));
+        push_snapshot(text("after")).unwrap();
+        for _ in 1..HOST_QUEUE_SIZE {
+            assert!(backend.wait_for_change());
+        }
+        assert!(backend.wait_for_change());
+        assert_eq!(backend.read("text/plain").as_deref(), Some(&b"after"[..]));
+        close_host();
+        assert!(!backend.wait_for_change());
+    }
+}
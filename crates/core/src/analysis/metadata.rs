@@ -0,0 +1,212 @@
+// 复制的文件的元数据和内容哈希
+// 元数据在分析时随文件一起读取；哈希不阻塞事件投递：文件事件先投递，由后台线程算完后以 Hashes 事件补发
+// 超过 MAX_HASH_BYTES 的文件不计算
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use super::{ClipboardEvent, ClipboardReport};
+use crate::global::{
+    FileHash, FileInfo, FileMetadata, HashesInfo, Selection, EVENT_SEQ, HASH_BLAKE3, HASH_SHA256, MAX_HASH_BYTES,
+};
+use crate::report_error_log;
+use crate::utils::now_millis;
+
+// 同时计算哈希的最大线程数
+const HASH_THREADS: usize = 4;
+const READ_BUFFER: usize = 64 * 1024;
+
+// 读取文件的元数据，文件不可访问时全部为空
+pub fn file_metadata(path: &Path) -> FileMetadata {
+    let Ok(metadata) = fs::metadata(path) else {
+        return FileMetadata::default();
+    };
+    let millis = |time: io::Result<SystemTime>| {
+        time.ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_millis() as u64)
+    };
+    let (uid, permissions) = owner(&metadata);
+    FileMetadata {
+        size: Some(metadata.len()),
+        modified_at: millis(metadata.modified()),
+        created_at: millis(metadata.created()),
+        uid,
+        permissions,
+        readonly: metadata.permissions().readonly(),
+    }
+}
+
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> (Option<u32>, Option<u32>) {
+    use std::os::unix::fs::MetadataExt;
+    (Some(metadata.uid()), Some(metadata.mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn owner(_metadata: &Metadata) -> (Option<u32>, Option<u32>) {
+    (None, None)
+}
+
+// 当前的哈希配置，两种算法都没有开启时为 None
+#[derive(Debug, Clone, Copy)]
+struct HashSettings {
+    sha256: bool,
+    blake3: bool,
+    max_bytes: u64,
+}
+
+impl HashSettings {
+    fn current() -> Option<Self> {
+        let sha256 = HASH_SHA256.load(Ordering::Relaxed);
+        let blake3 = HASH_BLAKE3.load(Ordering::Relaxed);
+        (sha256 || blake3).then(|| Self { sha256, blake3, max_bytes: MAX_HASH_BYTES.load(Ordering::Relaxed) })
+    }
+
+    // 压缩包中的条目和超过大小上限的文件跳过
+    fn pending(&self, files: &[FileInfo]) -> Vec<(String, String)> {
+        files
+            .iter()
+            .filter(|file| file.archive_entry.is_none() && file.metadata.size.is_some_and(|size| size <= self.max_bytes))
+            .map(|file| (file.path.clone(), file.category.clone()))
+            .collect()
+    }
+}
+
+// 一个文件事件的哈希任务，创建时读取当前配置
+#[derive(Debug)]
+pub struct HashJob {
+    settings: HashSettings,
+    // (路径, 分类)
+    files: Vec<(String, String)>,
+    event_seq: u64,
+    pid: u32,
+    pname: String,
+    selection: Selection,
+    formats: Vec<String>,
+}
+
+impl HashJob {
+    // 开启了哈希且文件事件中有需要计算的文件时创建
+    pub fn new(event: &ClipboardEvent) -> Option<Self> {
+        let ClipboardReport::Files(files) = &event.report else {
+            return None;
+        };
+        let settings = HashSettings::current()?;
+        let files = settings.pending(&files.files);
+        if files.is_empty() {
+            return None;
+        }
+        Some(Self {
+            settings,
+            files,
+            event_seq: event.seq,
+            pid: event.pid,
+            pname: event.pname.clone(),
+            selection: event.selection,
+            formats: event.formats.clone(),
+        })
+    }
+
+    // 计算全部哈希，阻塞到完成；cancel 置位后尽快放弃并返回 None
+    pub fn run(&self, cancel: &AtomicBool) -> Option<HashesInfo> {
+        let files = hash_all(&self.files, self.settings, cancel)?;
+        Some(HashesInfo { event_seq: self.event_seq, files })
+    }
+
+    // 把结果包装为后续事件，分配新的序号
+    pub fn into_event(self, hashes: HashesInfo) -> ClipboardEvent {
+        ClipboardEvent {
+            seq: EVENT_SEQ.fetch_add(1, Ordering::SeqCst) + 1,
+            timestamp: now_millis(),
+            pid: self.pid,
+            pname: self.pname,
+            selection: self.selection,
+            formats: self.formats,
+            report: ClipboardReport::Hashes(hashes),
+        }
+    }
+}
+
+// 同步计算文件事件的哈希并返回后续事件，供不经过 run_monitor 的调用方 (如 CLI 逐条回放) 使用
+pub fn hash_event(event: &ClipboardEvent) -> Option<ClipboardEvent> {
+    let job = HashJob::new(event)?;
+    let hashes = job.run(&AtomicBool::new(false))?;
+    Some(job.into_event(hashes))
+}
+
+// 按当前配置同步计算一组文件的哈希，没有开启哈希时为空
+pub fn hash_files(files: &[FileInfo]) -> Vec<FileHash> {
+    let Some(settings) = HashSettings::current() else {
+        return vec![];
+    };
+    hash_all(&settings.pending(files), settings, &AtomicBool::new(false)).unwrap_or_default()
+}
+
+// 最多 HASH_THREADS 个线程并行，结果与 files 的顺序一致，读取失败的文件跳过
+fn hash_all(files: &[(String, String)], settings: HashSettings, cancel: &AtomicBool) -> Option<Vec<FileHash>> {
+    let threads = files.len().min(HASH_THREADS);
+    let queue = Mutex::new(files.iter().enumerate());
+    let results = Mutex::new(vec![None; files.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let Some((index, (path, category))) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                match hash_file(Path::new(path), settings, cancel) {
+                    Ok(Some((sha256, blake3))) => {
+                        let hash = FileHash { path: path.clone(), category: category.clone(), sha256, blake3 };
+                        results.lock().unwrap()[index] = Some(hash);
+                    }
+                    Ok(None) => {}
+                    Err(_) if cancel.load(Ordering::Relaxed) => break,
+                    Err(e) => report_error_log!("failed to hash {}: {}", path, e),
+                }
+            });
+        }
+    });
+    if cancel.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some(results.into_inner().unwrap().into_iter().flatten().collect())
+}
+
+// 读取过程中文件变得超过上限时放弃，返回 None；cancel 置位时返回 Interrupted 错误
+fn hash_file(path: &Path, settings: HashSettings, cancel: &AtomicBool) -> io::Result<Option<(Option<String>, Option<String>)>> {
+    let max_bytes = settings.max_bytes;
+    let mut reader = File::open(path)?.take(max_bytes.saturating_add(1));
+    let mut sha256 = settings.sha256.then(Sha256::new);
+    let mut blake3 = settings.blake3.then(blake3::Hasher::new);
+    let mut buffer = vec![0u8; READ_BUFFER];
+    let mut total = 0u64;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        total += n as u64;
+        if total > max_bytes {
+            return Ok(None);
+        }
+        if let Some(hasher) = &mut sha256 {
+            hasher.update(&buffer[..n]);
+        }
+        if let Some(hasher) = &mut blake3 {
+            hasher.update(&buffer[..n]);
+        }
+    }
+    Ok(Some((
+        sha256.map(|hasher| format!("{:x}", hasher.finalize())),
+        blake3.map(|hasher| hasher.finalize().to_hex().to_string()),
+    )))
+}
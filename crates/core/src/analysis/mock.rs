@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use super::backend::{encode_drop_files, ClipboardBackend, FORMAT_DIB, FORMAT_HDROP};
+use crate::global::Selection;
+
+// 一次剪贴板内容快照：格式列表 (按写入顺序)、所有者进程和所属选区
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockSnapshot {
+    pub formats: Vec<(String, Vec<u8>)>,
+    pub owner: Option<(u32, String)>,
+    pub selection: Selection,
+}
+
+impl Default for MockSnapshot {
+    fn default() -> Self {
+        Self {
+            formats: vec![],
+            owner: None,
+            selection: Selection::CLIPBOARD,
+        }
+    }
+}
+
+impl MockSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(mut self, format: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.formats.push((format.to_string(), data.into()));
+        self
+    }
+
+    pub fn with_owner(mut self, pid: u32, pname: &str) -> Self {
+        self.owner = Some((pid, pname.to_string()));
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    // 格式名列表
+    pub fn format_names(&self) -> Vec<String> {
+        self.formats.iter().map(|(f, _)| f.clone()).collect()
+    }
+
+    // 读取指定格式的数据
+    pub fn read(&self, format: &str) -> Option<Vec<u8>> {
+        self.formats.iter().find(|(f, _)| f == format).map(|(_, data)| data.clone())
+    }
+
+    // 快捷构造：复制了一组文件 (CF_HDROP)
+    pub fn files<S: AsRef<str>>(paths: &[S]) -> Self {
+        Self::new().with_format(FORMAT_HDROP, encode_drop_files(paths))
+    }
+
+    // 快捷构造：复制了一张图片 (CF_DIB)
+    pub fn image(dib: impl Into<Vec<u8>>) -> Self {
+        Self::new().with_format(FORMAT_DIB, dib)
+    }
+}
+
+// 内存中的剪贴板后端：按顺序回放预先编排好的快照，快照用完即结束监听
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    script: VecDeque<MockSnapshot>,
+    current: MockSnapshot,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, snapshot: MockSnapshot) -> &mut Self {
+        self.script.push_back(snapshot);
+        self
+    }
+
+    // 剩余未回放的快照数量
+    pub fn pending(&self) -> usize {
+        self.script.len()
+    }
+}
+
+impl FromIterator<MockSnapshot> for MockBackend {
+    fn from_iter<I: IntoIterator<Item = MockSnapshot>>(iter: I) -> Self {
+        Self {
+            script: iter.into_iter().collect(),
+            current: MockSnapshot::default(),
+        }
+    }
+}
+
+impl ClipboardBackend for MockBackend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        Some(self.current.format_names())
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        self.current.read(format)
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        self.current.owner.clone()
+    }
+
+    fn selection(&self) -> Selection {
+        self.current.selection
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        match self.script.pop_front() {
+            Some(next) => {
+                self.current = next;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// 路径规则：按 glob 或正则包含 / 排除文件，与分类规则一起在 check_dropped_files 中生效
+// 匹配前路径统一使用 "/" 作为分隔符，Windows 的 "C:\Users\a" 按 "C:/Users/a" 匹配
+
+use regex::{Regex, RegexBuilder};
+
+use crate::error::MonitorError;
+
+// 默认是否区分大小写：与文件系统一致，Windows / macOS 不区分
+pub const DEFAULT_CASE_SENSITIVE: bool = cfg!(not(any(windows, target_os = "macos")));
+
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    // 用户传入的原始规则，用于日志
+    pub source: String,
+    regex: Regex,
+}
+
+impl PathPattern {
+    // glob 规则：* 和 ? 不跨目录，** 匹配任意多级目录，支持 [abc] 和 {a,b}
+    // 不含 "/" 的规则匹配任意层级的同名文件或目录 (如 "node_modules")，开头的 "~" 展开为用户目录
+    // 规则匹配到某个上级目录时，目录下的全部文件都算匹配
+    pub fn glob(pattern: &str, case_sensitive: bool) -> Result<Self, MonitorError> {
+        let mut glob = normalize_path(pattern.trim());
+        if let Some(rest) = glob.strip_prefix('~') {
+            if rest.is_empty() || rest.starts_with('/') {
+                let home = home_dir().ok_or_else(|| {
+                    MonitorError::new(format!("Cannot expand \"~\" in path rule {:?}, home directory is unknown", pattern))
+                })?;
+                glob = format!("{}{}", normalize_path(&home).trim_end_matches('/'), rest);
+            }
+        }
+        let glob = glob.trim_end_matches('/');
+        if glob.is_empty() {
+            return Err(MonitorError::new(format!("Empty path rule {:?}", pattern)));
+        }
+        let glob = if glob.contains('/') { glob.to_string() } else { format!("**/{}", glob) };
+        let expr = glob_to_regex(&glob).map_err(|e| MonitorError::new(format!("Invalid glob {:?}: {}", pattern, e)))?;
+        Self::build(pattern, &expr, case_sensitive)
+    }
+
+    // 正则规则：在整个路径中查找，需要整体匹配时自行加 ^ / $
+    pub fn regex(pattern: &str, case_sensitive: bool) -> Result<Self, MonitorError> {
+        Self::build(pattern, pattern, case_sensitive)
+    }
+
+    fn build(source: &str, expr: &str, case_sensitive: bool) -> Result<Self, MonitorError> {
+        let regex = RegexBuilder::new(expr)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| MonitorError::new(format!("Invalid path rule {:?}: {}", source, e)))?;
+        Ok(Self { source: source.to_string(), regex })
+    }
+
+    // path 需先经过 normalize_path
+    pub fn matches(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+// 包含 / 排除规则，包含规则为空时不限制来源目录
+#[derive(Debug, Clone, Default)]
+pub struct PathRules {
+    pub include: Vec<PathPattern>,
+    pub exclude: Vec<PathPattern>,
+}
+
+impl PathRules {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    // 命中的第一条排除规则，遍历文件夹时命中排除规则的子目录整个跳过
+    pub fn excluded_by(&self, path: &str) -> Option<&PathPattern> {
+        if self.exclude.is_empty() {
+            return None;
+        }
+        let path = normalize_path(path);
+        self.exclude.iter().find(|p| p.matches(&path))
+    }
+
+    // 命中排除规则，或设置了包含规则却一条都没命中时，返回拦下该路径的原因
+    pub fn reject_reason(&self, path: &str) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        if let Some(pattern) = self.excluded_by(path) {
+            return Some(format!("excluded by path rule {:?}", pattern.source));
+        }
+        let path = normalize_path(path);
+        if !self.include.is_empty() && !self.include.iter().any(|p| p.matches(&path)) {
+            return Some(String::from("not under any included path"));
+        }
+        None
+    }
+}
+
+pub fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+fn home_dir() -> Option<String> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var(var).ok().filter(|home| !home.is_empty())
+}
+
+// glob 转为锚定的正则，结尾额外允许 "/..." 使规则也匹配目录下的文件
+fn glob_to_regex(glob: &str) -> Result<String, String> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut expr = String::from("^");
+    let mut braces = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                if chars.get(i + 1) == Some(&'/') {
+                    // "**/" 匹配零或多级目录
+                    i += 1;
+                    expr.push_str("(?:.*/)?");
+                } else {
+                    expr.push_str(".*");
+                }
+            }
+            '*' => expr.push_str("[^/]*"),
+            '?' => expr.push_str("[^/]"),
+            '[' => {
+                let end = chars[i + 1..].iter().position(|&c| c == ']').ok_or("unclosed \"[\"")? + i + 1;
+                let mut class: String = chars[i + 1..end].iter().collect();
+                if let Some(rest) = class.strip_prefix('!') {
+                    class = format!("^{}", rest);
+                }
+                expr.push('[');
+                expr.push_str(&class.replace('[', "\\["));
+                expr.push(']');
+                i = end;
+            }
+            '{' => {
+                braces += 1;
+                expr.push_str("(?:");
+            }
+            '}' if braces > 0 => {
+                braces -= 1;
+                expr.push(')');
+            }
+            ',' if braces > 0 => expr.push('|'),
+            c => expr.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+        i += 1;
+    }
+    if braces > 0 {
+        return Err(String::from("unclosed \"{\""));
+    }
+    expr.push_str("(?:/.*)?$");
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> PathPattern {
+        PathPattern::glob(pattern, true).unwrap()
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        let cases = [
+            // * 和 ? 不跨目录
+            ("/work/*.rs", "/work/main.rs", true),
+            ("/work/*.rs", "/work/src/main.rs", false),
+            ("/work/?.txt", "/work/a.txt", true),
+            ("/work/?.txt", "/work/ab.txt", false),
+            ("/work/?", "/work/a/b", true),
+            // ** 匹配零或多级目录
+            ("/work/**/*.rs", "/work/main.rs", true),
+            ("/work/**/*.rs", "/work/a/b/c/main.rs", true),
+            ("/work/**", "/work/a/b", true),
+            ("/work/**", "/workspace/a", false),
+            // 字符类和候选
+            ("/work/[ab].txt", "/work/b.txt", true),
+            ("/work/[!ab].txt", "/work/b.txt", false),
+            ("/work/[!ab].txt", "/work/c.txt", true),
+            ("/work/*.{xls,xlsx}", "/work/q.xlsx", true),
+            ("/work/*.{xls,xlsx}", "/work/q.csv", false),
+        ];
+        for (pattern, path, expected) in cases {
+            assert_eq!(glob(pattern).matches(path), expected, "{} vs {}", pattern, path);
+        }
+    }
+
+    #[test]
+    fn glob_matches_directories_and_bare_names() {
+        // 匹配到目录时目录下的文件都算匹配，但不匹配同前缀的兄弟目录
+        let dir = glob("/work/secret/");
+        assert!(dir.matches("/work/secret"));
+        assert!(dir.matches("/work/secret/a/b.txt"));
+        assert!(!dir.matches("/work/secrets/a.txt"));
+        // 不含 "/" 的规则匹配任意层级
+        let name = glob("node_modules");
+        assert!(name.matches("/repo/node_modules/pkg/index.js"));
+        assert!(name.matches("node_modules"));
+        assert!(!name.matches("/repo/my_node_modules/a.js"));
+        assert!(glob("*.key").matches("/home/a/.ssh/id.key"));
+    }
+
+    #[test]
+    fn glob_escapes_regex_characters() {
+        let pattern = glob("/work/a+b (1).txt");
+        assert!(pattern.matches("/work/a+b (1).txt"));
+        assert!(!pattern.matches("/work/aab (1).txt"));
+        assert!(!glob("/work/a.txt").matches("/work/abtxt"));
+        assert!(glob("/work/$HOME^").matches("/work/$HOME^"));
+    }
+
+    #[test]
+    fn invalid_globs_are_rejected() {
+        for pattern in ["", "  ", "/", "/work/[abc", "/work/{a,b"] {
+            assert!(PathPattern::glob(pattern, true).is_err(), "{:?}", pattern);
+        }
+        assert!(PathPattern::regex("(unclosed", true).is_err());
+    }
+
+    #[test]
+    fn case_sensitivity_and_separators() {
+        assert!(!glob("/Work/*.XLSX").matches("/work/a.xlsx"));
+        assert!(PathPattern::glob("/Work/*.XLSX", false).unwrap().matches("/work/a.xlsx"));
+        // Windows 路径按 "/" 分隔后匹配
+        let rules = PathRules { include: vec![], exclude: vec![PathPattern::glob(r"C:\Users\*\AppData", false).unwrap()] };
+        assert!(rules.excluded_by(r"C:\Users\a\AppData\Local\x.db").is_some());
+        assert!(rules.excluded_by(r"C:\Users\a\Documents\x.db").is_none());
+    }
+
+    #[test]
+    fn include_and_exclude_rules() {
+        let rules = PathRules {
+            include: vec![glob("/work/**"), PathPattern::regex(r"\.xlsx$", true).unwrap()],
+            exclude: vec![glob("**/node_modules")],
+        };
+        assert_eq!(rules.reject_reason("/work/src/main.rs"), None);
+        assert_eq!(rules.reject_reason("/tmp/report.xlsx"), None);
+        assert_eq!(rules.reject_reason("/tmp/notes.txt").as_deref(), Some("not under any included path"));
+        assert_eq!(
+            rules.reject_reason("/work/node_modules/a.js").as_deref(),
+            Some("excluded by path rule \"**/node_modules\"")
+        );
+        assert_eq!(PathRules::default().reject_reason("/anything"), None);
+    }
+}
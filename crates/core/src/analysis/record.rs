@@ -0,0 +1,443 @@
+// 剪贴板会话的录制与回放：把每次经过分析的变化写入文件，之后可离线重新跑一遍分析
+// 只录制分析时读取的格式数据，其余格式只记录名称 (数据为空)；被节流跳过的变化不录制
+//
+// 文件格式 (全部小端)：
+//   文件头   MAGIC (8 字节)
+//   每条记录 时间戳毫秒 u64 | 选区 u8 | 是否有所有者 u8 [| pid u32 | 进程名 str] | 格式数 u32 | (格式名 str | 数据 bytes)*
+//   str / bytes 为 u32 长度前缀 + 原始字节
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use super::backend::ClipboardBackend;
+use super::mock::MockSnapshot;
+use crate::error::MonitorError;
+use crate::global::Selection;
+use crate::utils::now_millis;
+
+const MAGIC: &[u8; 8] = b"CCAREC\x00\x01";
+
+// 一条录制记录：剪贴板快照及其发生时间
+#[derive(Debug, Clone)]
+pub struct RecordedSnapshot {
+    // Unix 时间戳 (毫秒)
+    pub timestamp: u64,
+    pub snapshot: MockSnapshot,
+}
+
+fn selection_tag(selection: Selection) -> u8 {
+    match selection {
+        Selection::CLIPBOARD => 0,
+        Selection::PRIMARY => 1,
+        Selection::SECONDARY => 2,
+    }
+}
+
+fn selection_from_tag(tag: u8) -> io::Result<Selection> {
+    match tag {
+        0 => Ok(Selection::CLIPBOARD),
+        1 => Ok(Selection::PRIMARY),
+        2 => Ok(Selection::SECONDARY),
+        _ => Err(io::Error::new(ErrorKind::InvalidData, format!("unknown selection tag {}", tag))),
+    }
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len()).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "field too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_u32(reader)? as usize;
+    let mut buf = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    Ok(buf)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    String::from_utf8(read_bytes(reader)?).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+// 录制文件写入器
+pub struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, record: &RecordedSnapshot) -> io::Result<()> {
+        let snapshot = &record.snapshot;
+        self.writer.write_all(&record.timestamp.to_le_bytes())?;
+        self.writer.write_all(&[selection_tag(snapshot.selection)])?;
+        match &snapshot.owner {
+            Some((pid, pname)) => {
+                self.writer.write_all(&[1])?;
+                self.writer.write_all(&pid.to_le_bytes())?;
+                write_bytes(&mut self.writer, pname.as_bytes())?;
+            }
+            None => self.writer.write_all(&[0])?,
+        }
+        self.writer.write_all(&(snapshot.formats.len() as u32).to_le_bytes())?;
+        for (format, data) in &snapshot.formats {
+            write_bytes(&mut self.writer, format.as_bytes())?;
+            write_bytes(&mut self.writer, data)?;
+        }
+        // 每条记录都落盘，进程被强制结束时也不会丢失已录制的内容
+        self.writer.flush()
+    }
+}
+
+// 读取整个录制文件
+pub fn read_recording<R: Read>(mut reader: R) -> io::Result<Vec<RecordedSnapshot>> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(ErrorKind::InvalidData, "not a clipboard recording"));
+    }
+
+    let mut records = vec![];
+    loop {
+        // 文件在记录边界处结束是正常的
+        let mut timestamp = [0u8; 8];
+        match reader.read_exact(&mut timestamp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+
+        let mut snapshot = MockSnapshot::new().with_selection(selection_from_tag(read_u8(&mut reader)?)?);
+        if read_u8(&mut reader)? != 0 {
+            let pid = read_u32(&mut reader)?;
+            snapshot.owner = Some((pid, read_string(&mut reader)?));
+        }
+        let count = read_u32(&mut reader)?;
+        for _ in 0..count {
+            let format = read_string(&mut reader)?;
+            snapshot.formats.push((format, read_bytes(&mut reader)?));
+        }
+        records.push(RecordedSnapshot {
+            timestamp: u64::from_le_bytes(timestamp),
+            snapshot,
+        });
+    }
+    Ok(records)
+}
+
+// 正在进行的一次变化：真实后端的内容在分析读取时才取，不读取分析用不到的格式
+struct Change {
+    record: RecordedSnapshot,
+    // None 表示还没有分析 (被节流跳过)，Some(false) 表示剪贴板暂时无法读取；只有 Some(true) 写入录制
+    readable: Option<bool>,
+    // 已经从真实后端读取过的格式
+    fetched: HashSet<String>,
+    owner_fetched: bool,
+}
+
+// 录制后端：包装真实后端，把分析时读取到的内容记入当前变化，下一次变化开始或后端释放时写入文件
+// 分析逻辑读取的是记下来的快照，保证回放时看到的数据与现场完全一致
+pub struct RecordingBackend<B: ClipboardBackend + ?Sized, W: Write> {
+    recorder: Recorder<W>,
+    current: Option<Change>,
+    inner: Box<B>,
+}
+
+impl<B: ClipboardBackend + ?Sized, W: Write> RecordingBackend<B, W> {
+    pub fn new(inner: Box<B>, recorder: Recorder<W>) -> Self {
+        Self {
+            recorder,
+            current: None,
+            inner,
+        }
+    }
+
+    // 写入已经分析过的当前变化
+    fn flush(&mut self) {
+        let Some(change) = self.current.take() else {
+            return;
+        };
+        if change.readable != Some(true) {
+            return;
+        }
+        if let Err(e) = self.recorder.write(&change.record) {
+            crate::report_error_log!("Failed to write clipboard recording: {}", e);
+        }
+    }
+}
+
+impl<B: ClipboardBackend + ?Sized, W: Write> Drop for RecordingBackend<B, W> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<B: ClipboardBackend + ?Sized, W: Write> ClipboardBackend for RecordingBackend<B, W> {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        let change = self.current.as_mut()?;
+        if change.readable.is_none() {
+            let formats = self.inner.formats();
+            change.readable = Some(formats.is_some());
+            change.record.snapshot.formats = formats.unwrap_or_default().into_iter().map(|format| (format, vec![])).collect();
+        }
+        (change.readable == Some(true)).then(|| change.record.snapshot.format_names())
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        let change = self.current.as_mut()?;
+        if change.readable == Some(true) && change.fetched.insert(format.to_string()) {
+            if let Some((_, data)) = change.record.snapshot.formats.iter_mut().find(|(f, _)| f == format) {
+                // 读取失败的格式 (如只有句柄的 CF_BITMAP) 记为空数据，保留格式存在这一事实
+                *data = self.inner.read(format).unwrap_or_default();
+            }
+        }
+        change.record.snapshot.read(format)
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        let change = self.current.as_mut()?;
+        if !change.owner_fetched {
+            change.owner_fetched = true;
+            change.record.snapshot.owner = self.inner.owner();
+        }
+        change.record.snapshot.owner.clone()
+    }
+
+    fn knows_owner(&self) -> bool {
+        self.inner.knows_owner()
+    }
+
+    // 与写入录制的时间一致，回放得到的事件时间与现场相同
+    fn changed_at(&self) -> Option<u64> {
+        self.current.as_ref().map(|change| change.record.timestamp)
+    }
+
+    fn selection(&self) -> Selection {
+        self.current.as_ref().map_or_else(|| self.inner.selection(), |change| change.record.snapshot.selection)
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        self.flush();
+        if !self.inner.wait_for_change() {
+            return false;
+        }
+        self.current = Some(Change {
+            record: RecordedSnapshot {
+                timestamp: now_millis(),
+                snapshot: MockSnapshot::new().with_selection(self.inner.selection()),
+            },
+            readable: None,
+            fetched: HashSet::new(),
+            owner_fetched: false,
+        });
+        true
+    }
+
+    fn take_error(&mut self) -> Option<MonitorError> {
+        self.inner.take_error()
+    }
+}
+
+// 回放后端：按顺序重放录制文件中的快照
+// 默认不等待，立即给出下一条；realtime 模式按录制时的间隔等待，让节流逻辑与现场表现一致
+#[derive(Debug, Default)]
+pub struct ReplayBackend {
+    records: std::vec::IntoIter<RecordedSnapshot>,
+    current: Option<RecordedSnapshot>,
+    realtime: bool,
+}
+
+impl ReplayBackend {
+    pub fn new(records: Vec<RecordedSnapshot>) -> Self {
+        Self {
+            records: records.into_iter(),
+            current: None,
+            realtime: false,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(read_recording(BufReader::new(File::open(path)?))?))
+    }
+
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    // 当前回放到的记录
+    pub fn current(&self) -> Option<&RecordedSnapshot> {
+        self.current.as_ref()
+    }
+
+    fn snapshot(&self) -> Option<&MockSnapshot> {
+        self.current.as_ref().map(|r| &r.snapshot)
+    }
+}
+
+impl ClipboardBackend for ReplayBackend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        Some(self.snapshot().map(MockSnapshot::format_names).unwrap_or_default())
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        self.snapshot()?.read(format)
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        self.snapshot()?.owner.clone()
+    }
+
+    // 事件使用录制时的时间，而不是回放的时间
+    fn changed_at(&self) -> Option<u64> {
+        self.current.as_ref().map(|record| record.timestamp)
+    }
+
+    fn selection(&self) -> Selection {
+        self.snapshot().map(|s| s.selection).unwrap_or(Selection::CLIPBOARD)
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        let Some(next) = self.records.next() else {
+            return false;
+        };
+        if self.realtime {
+            if let Some(prev) = &self.current {
+                thread::sleep(Duration::from_millis(next.timestamp.saturating_sub(prev.timestamp)));
+            }
+        }
+        self.current = Some(next);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::analysis::analyze_clipboard;
+    use crate::analysis::mock::MockBackend;
+    use crate::global::LAST_IMG_SIZE;
+    use crate::utils::{lock_globals, test_dir};
+
+    fn snapshots() -> Vec<MockSnapshot> {
+        vec![
+            MockSnapshot::new().with_format("text/plain", "first copy").with_owner(42, "editor"),
+            MockSnapshot::image(vec![7u8; 64]).with_selection(Selection::PRIMARY),
+            MockSnapshot::new(),
+        ]
+    }
+
+    #[test]
+    fn recording_round_trips_through_replay() {
+        let _globals = lock_globals();
+        let dir = test_dir("record");
+        let path = dir.join("session.ccarec");
+
+        // 录制：直接逐条分析，不经过节流
+        let mut recording = RecordingBackend::new(
+            Box::new(snapshots().into_iter().collect::<MockBackend>()),
+            Recorder::create(&path).unwrap(),
+        );
+        let mut recorded = vec![];
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        while recording.wait_for_change() {
+            recorded.extend(analyze_clipboard(&mut recording));
+        }
+        drop(recording);
+
+        let records = read_recording(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let read: Vec<MockSnapshot> = records.iter().map(|record| record.snapshot.clone()).collect();
+        assert_eq!(read, snapshots());
+        assert!(records.windows(2).all(|pair| pair[1].timestamp >= pair[0].timestamp));
+
+        // 回放：事件内容和时间与录制时一致
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        let mut replay = ReplayBackend::open(&path).unwrap();
+        let mut replayed = vec![];
+        while replay.wait_for_change() {
+            replayed.extend(analyze_clipboard(&mut replay));
+        }
+        let _ = fs::remove_dir_all(dir);
+
+        assert_eq!(recorded.len(), 3);
+        assert_eq!(replayed.len(), recorded.len());
+        for ((replayed, recorded), record) in replayed.iter().zip(&recorded).zip(&records) {
+            assert_eq!(replayed.report.kind(), recorded.report.kind());
+            assert_eq!(replayed.timestamp, record.timestamp);
+            assert_eq!(recorded.timestamp, record.timestamp);
+            assert_eq!((replayed.pid, &replayed.pname), (recorded.pid, &recorded.pname));
+            assert_eq!(replayed.selection, recorded.selection);
+            assert_eq!(replayed.formats, recorded.formats);
+        }
+    }
+
+    #[test]
+    fn only_analyzed_changes_and_read_formats_are_recorded() {
+        let _globals = lock_globals();
+        let copied = MockSnapshot::new()
+            .with_format("text/plain", "analyzed")
+            .with_format("application/x-large-blob", vec![1u8; 4096]);
+        let throttled = MockSnapshot::new().with_format("text/plain", "throttled");
+        let mut bytes = vec![];
+        let mut recording = RecordingBackend::new(
+            Box::new([copied, throttled].into_iter().collect::<MockBackend>()),
+            Recorder::new(&mut bytes).unwrap(),
+        );
+        assert!(recording.wait_for_change());
+        assert!(analyze_clipboard(&mut recording).is_some());
+        // 第二次变化被节流，不分析
+        assert!(recording.wait_for_change());
+        assert!(!recording.wait_for_change());
+        drop(recording);
+
+        let records = read_recording(bytes.as_slice()).unwrap();
+        assert_eq!(records.len(), 1);
+        // 分析用不到的格式只记录名称
+        let expected = MockSnapshot::new()
+            .with_format("text/plain", "analyzed")
+            .with_format("application/x-large-blob", vec![]);
+        assert_eq!(records[0].snapshot, expected);
+    }
+
+    #[test]
+    fn truncated_recording_is_rejected() {
+        let mut bytes = vec![];
+        let mut recorder = Recorder::new(&mut bytes).unwrap();
+        let snapshot = MockSnapshot::new().with_format("text/plain", "cut short");
+        recorder.write(&RecordedSnapshot { timestamp: 1, snapshot }).unwrap();
+        bytes.truncate(bytes.len() - 3);
+        assert_eq!(read_recording(bytes.as_slice()).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(read_recording(&b"NOTAREC!"[..]).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
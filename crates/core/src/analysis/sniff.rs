@@ -0,0 +1,454 @@
+// 按文件头 (magic bytes) 识别文件的真实类型，不依赖扩展名
+// 只读取文件开头的少量字节；OLE2 复合文档额外读取一个目录扇区来区分 Excel / Word / PowerPoint
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+// 读取的文件头大小，OOXML 开头几个条目的本地文件头通常都在这个范围内
+pub const HEADER_BYTES: usize = 16 * 1024;
+// 本地文件头没有走完时读取的中央目录的大小上限
+const CENTRAL_DIRECTORY_BYTES: u64 = 1024 * 1024;
+// EOCD 固定部分的大小和注释的最大长度
+const EOCD_BYTES: u64 = 22;
+const MAX_ZIP_COMMENT: u64 = 0xFFFF;
+
+// 识别出的内容类型及其常见的扩展名 (带点号、小写)，扩展名不在列表中即视为不匹配
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContentType {
+    pub mime: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+pub const PNG: ContentType = ContentType { mime: "image/png", extensions: &[".png"] };
+pub const JPEG: ContentType = ContentType { mime: "image/jpeg", extensions: &[".jpg", ".jpeg", ".jpe", ".jfif"] };
+pub const GIF: ContentType = ContentType { mime: "image/gif", extensions: &[".gif"] };
+pub const WEBP: ContentType = ContentType { mime: "image/webp", extensions: &[".webp"] };
+pub const BMP: ContentType = ContentType { mime: "image/bmp", extensions: &[".bmp", ".dib"] };
+pub const TIFF: ContentType = ContentType { mime: "image/tiff", extensions: &[".tif", ".tiff"] };
+pub const PDF: ContentType = ContentType { mime: "application/pdf", extensions: &[".pdf"] };
+pub const XLSX: ContentType = ContentType {
+    mime: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    extensions: &[".xlsx", ".xlsm", ".xltx", ".xltm", ".xlam"],
+};
+pub const DOCX: ContentType = ContentType {
+    mime: "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    extensions: &[".docx", ".docm", ".dotx", ".dotm"],
+};
+pub const PPTX: ContentType = ContentType {
+    mime: "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    extensions: &[".pptx", ".pptm", ".potx", ".potm", ".ppsx"],
+};
+// 有 [Content_Types].xml 但读不到主文档部件 (如流式写入、文件被截断) 时，只知道是某种 OOXML 文档
+pub const OOXML: ContentType = ContentType {
+    mime: "application/vnd.openxmlformats-officedocument",
+    extensions: &[
+        ".xlsx", ".xlsm", ".xltx", ".xltm", ".xlam", ".docx", ".docm", ".dotx", ".dotm", ".pptx", ".pptm", ".potx", ".potm",
+        ".ppsx",
+    ],
+};
+pub const ZIP: ContentType = ContentType { mime: "application/zip", extensions: &[".zip", ".jar", ".apk"] };
+pub const TAR: ContentType = ContentType { mime: "application/x-tar", extensions: &[".tar"] };
+// .tar.gz 的后缀取最后一段 ".gz"
+pub const GZIP: ContentType = ContentType { mime: "application/gzip", extensions: &[".gz", ".tgz"] };
+pub const SEVEN_Z: ContentType = ContentType { mime: "application/x-7z-compressed", extensions: &[".7z"] };
+pub const XLS: ContentType = ContentType { mime: "application/vnd.ms-excel", extensions: &[".xls", ".xlt", ".xla"] };
+pub const DOC: ContentType = ContentType { mime: "application/msword", extensions: &[".doc", ".dot"] };
+pub const PPT: ContentType = ContentType { mime: "application/vnd.ms-powerpoint", extensions: &[".ppt", ".pot", ".pps"] };
+pub const OLE2: ContentType = ContentType { mime: "application/x-ole-storage", extensions: &[".msg", ".msi"] };
+// Linux 可执行文件通常没有扩展名
+pub const ELF: ContentType = ContentType { mime: "application/x-elf", extensions: &["", ".so", ".o", ".bin"] };
+pub const PE: ContentType = ContentType {
+    mime: "application/vnd.microsoft.portable-executable",
+    extensions: &[".exe", ".dll", ".sys", ".scr", ".ocx", ".cpl", ".efi"],
+};
+
+// BITMAPCOREHEADER、BITMAPINFOHEADER 及其各版本扩展的大小
+const DIB_HEADER_SIZES: [u32; 7] = [12, 40, 52, 56, 64, 108, 124];
+const OLE2_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+impl ContentType {
+    // 声明的扩展名 (带点号、小写，没有扩展名时为空串) 是否与内容一致
+    pub fn matches_extension(&self, extension: &str) -> bool {
+        self.extensions.contains(&extension)
+    }
+}
+
+fn utf16(name: &str) -> Vec<u8> {
+    name.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn le_u16(bytes: &[u8], offset: usize) -> Option<usize> {
+    bytes.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+}
+
+fn le_u32(bytes: &[u8], offset: usize) -> Option<usize> {
+    bytes.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+// 依次读取文件头范围内的 ZIP 本地文件头，返回条目名称和是否走完了全部本地文件头
+// 使用数据描述符 (标志位 3) 的条目不知道压缩后的大小，无法跳到下一个条目，在此停止
+// 流式写入的 ZIP (Java ZipOutputStream、Python zipfile 写入流) 每个条目都有该标志
+fn zip_entry_names(header: &[u8]) -> (Vec<&[u8]>, bool) {
+    let mut names = vec![];
+    let mut offset = 0;
+    while header.get(offset..offset + 4) == Some(b"PK\x03\x04") {
+        let (Some(flags), Some(compressed), Some(name_len), Some(extra_len)) =
+            (le_u16(header, offset + 6), le_u32(header, offset + 18), le_u16(header, offset + 26), le_u16(header, offset + 28))
+        else {
+            return (names, false);
+        };
+        let name_start = offset + 30;
+        let Some(name) = header.get(name_start..name_start + name_len) else {
+            return (names, false);
+        };
+        names.push(name);
+        if flags & 0x08 != 0 {
+            return (names, false);
+        }
+        offset = name_start + name_len + extra_len + compressed;
+    }
+    // 停在文件头范围内的其他记录 (中央目录) 上才算走完
+    (names, header.len() >= offset + 4)
+}
+
+// 从文件末尾的 EOCD 找到中央目录，返回全部条目名称
+// ZIP64 和超过 CENTRAL_DIRECTORY_BYTES 的中央目录不读取
+fn central_directory_names<R: Read + Seek>(reader: &mut R) -> Option<Vec<Vec<u8>>> {
+    let len = reader.seek(SeekFrom::End(0)).ok()?;
+    let tail_len = len.min(EOCD_BYTES + MAX_ZIP_COMMENT);
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.seek(SeekFrom::Start(len - tail_len)).ok()?;
+    reader.read_exact(&mut tail).ok()?;
+    let eocd = tail.windows(4).rposition(|window| window == b"PK\x05\x06")?;
+    let (size, offset) = (le_u32(&tail, eocd + 12)? as u64, le_u32(&tail, eocd + 16)? as u64);
+    if size == 0xFFFF_FFFF || offset == 0xFFFF_FFFF || size > CENTRAL_DIRECTORY_BYTES || offset + size > len {
+        return None;
+    }
+    let mut directory = vec![0u8; size as usize];
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    reader.read_exact(&mut directory).ok()?;
+
+    let mut names = vec![];
+    let mut offset = 0;
+    while directory.get(offset..offset + 4) == Some(b"PK\x01\x02") {
+        let name_len = le_u16(&directory, offset + 28)?;
+        let extra_len = le_u16(&directory, offset + 30)?;
+        let comment_len = le_u16(&directory, offset + 32)?;
+        let name_start = offset + 46;
+        names.push(directory.get(name_start..name_start + name_len)?.to_vec());
+        offset = name_start + name_len + extra_len + comment_len;
+    }
+    Some(names)
+}
+
+// 按条目名称判断 OOXML 文档的类型，没有 [Content_Types].xml 时返回 None
+fn ooxml_type<N: AsRef<[u8]>>(names: &[N]) -> Option<&'static ContentType> {
+    if !names.iter().any(|name| name.as_ref() == b"[Content_Types].xml") {
+        return None;
+    }
+    let has_part = |prefix: &[u8]| names.iter().any(|name| name.as_ref().starts_with(prefix));
+    if has_part(b"xl/workbook.") {
+        Some(&XLSX)
+    } else if has_part(b"word/document.") {
+        Some(&DOCX)
+    } else if has_part(b"ppt/presentation.") {
+        Some(&PPTX)
+    } else {
+        Some(&ZIP)
+    }
+}
+
+// ZIP 中有 [Content_Types].xml 时是 OOXML 文档，再按主文档部件的路径区分
+// 文件头中的本地文件头没有走完时改读中央目录；中央目录也读不到时，有 [Content_Types].xml 即视为 OOXML
+fn sniff_zip<R: Read + Seek>(reader: &mut R, header: &[u8]) -> &'static ContentType {
+    let (names, complete) = zip_entry_names(header);
+    match ooxml_type(&names) {
+        Some(content) if *content != ZIP => return content,
+        _ if complete => return &ZIP,
+        _ => {}
+    }
+    if let Some(names) = central_directory_names(reader) {
+        return ooxml_type(&names).unwrap_or(&ZIP);
+    }
+    if ooxml_type(&names).is_some() {
+        &OOXML
+    } else {
+        &ZIP
+    }
+}
+
+// OLE2：读取第一个目录扇区，按流名称区分 Office 97-2003 文档
+fn sniff_ole2<R: Read + Seek>(reader: &mut R, header: &[u8]) -> &'static ContentType {
+    let read_u32 = |offset: usize| header.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let sector_shift = header.get(0x1E..0x20).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let (Some(shift @ 9..=12), Some(dir_sector)) = (sector_shift, read_u32(0x30)) else {
+        return &OLE2;
+    };
+    let sector_size = 1u64 << shift;
+    let mut directory = vec![0u8; sector_size as usize];
+    let offset = (dir_sector as u64 + 1) * sector_size;
+    if reader.seek(SeekFrom::Start(offset)).is_err() || reader.read_exact(&mut directory).is_err() {
+        return &OLE2;
+    }
+
+    // 目录项 128 字节，开头是 UTF-16 的名称
+    let names: Vec<&[u8]> = directory.chunks_exact(128).map(|entry| &entry[..64]).collect();
+    let has_stream = |name: &str| {
+        let name = utf16(name);
+        names.iter().any(|entry| entry.starts_with(&name) && entry[name.len()..].starts_with(&[0, 0]))
+    };
+    if has_stream("Workbook") || has_stream("Book") {
+        &XLS
+    } else if has_stream("WordDocument") {
+        &DOC
+    } else if has_stream("PowerPoint Document") {
+        &PPT
+    } else {
+        &OLE2
+    }
+}
+
+// 从可定位的数据源识别内容类型，无法识别时返回 None
+pub fn sniff<R: Read + Seek>(reader: &mut R) -> Option<&'static ContentType> {
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    reader.by_ref().take(HEADER_BYTES as u64).read_to_end(&mut header).ok()?;
+    let h = header.as_slice();
+
+    let content = if h.starts_with(b"\x89PNG\r\n\x1a\n") {
+        &PNG
+    } else if h.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        &JPEG
+    } else if h.starts_with(b"GIF87a") || h.starts_with(b"GIF89a") {
+        &GIF
+    } else if h.starts_with(b"RIFF") && h.get(8..12) == Some(b"WEBP") {
+        &WEBP
+    } else if h.starts_with(b"II*\0") || h.starts_with(b"MM\0*") {
+        &TIFF
+    } else if h.starts_with(b"%PDF-") {
+        &PDF
+    } else if h.starts_with(b"PK\x03\x04") || h.starts_with(b"PK\x05\x06") {
+        sniff_zip(reader, h)
+    } else if h.starts_with(&[0x1F, 0x8B]) {
+        &GZIP
+    } else if h.starts_with(b"7z\xBC\xAF\x27\x1C") {
+        &SEVEN_Z
+    } else if h.starts_with(&OLE2_MAGIC) {
+        sniff_ole2(reader, h)
+    } else if h.starts_with(b"\x7fELF") {
+        &ELF
+    } else if h.starts_with(b"MZ") && is_pe(h) {
+        &PE
+    } else if h.get(257..262) == Some(b"ustar") {
+        // tar 没有文件开头的 magic，POSIX 格式在第一个条目头的 257 字节处
+        &TAR
+    } else if h.starts_with(b"BM") && h.get(14..18).is_some_and(|b| DIB_HEADER_SIZES.contains(&u32::from_le_bytes([b[0], b[1], b[2], b[3]]))) {
+        // BMP 头部只有两个字节，额外检查 DIB 头大小避免把以 "BM" 开头的文本误判为图片
+        &BMP
+    } else {
+        return None;
+    };
+    Some(content)
+}
+
+// MZ 头的 e_lfanew 指向 "PE\0\0"，头部过大的文件只凭 MZ 判断
+fn is_pe(header: &[u8]) -> bool {
+    let Some(offset) = header.get(0x3C..0x40).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize) else {
+        return false;
+    };
+    match header.get(offset..offset + 4) {
+        Some(signature) => signature == b"PE\0\0",
+        None => offset >= header.len(),
+    }
+}
+
+// 读取文件头识别内容类型，文件不存在、是目录或无法读取时返回 None
+pub fn sniff_file(path: impl AsRef<Path>) -> Option<&'static ContentType> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return None;
+    }
+    sniff(&mut File::open(path).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    use super::*;
+    use crate::analysis::classify_with;
+    use crate::global::FileRules;
+    use crate::utils::test_dir;
+
+    fn sniff_bytes(bytes: &[u8]) -> Option<&'static ContentType> {
+        sniff(&mut Cursor::new(bytes))
+    }
+
+    fn zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, data) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    // 只有一个目录扇区的 OLE2 文件头，streams 为目录项名称
+    fn ole2_with(streams: &[&str]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 512 * 2];
+        bytes[..8].copy_from_slice(&OLE2_MAGIC);
+        bytes[0x1E..0x20].copy_from_slice(&9u16.to_le_bytes());
+        bytes[0x30..0x34].copy_from_slice(&0u32.to_le_bytes());
+        for (index, name) in ["Root Entry"].iter().chain(streams).enumerate() {
+            let name = utf16(name);
+            let entry = 512 + index * 128;
+            bytes[entry..entry + name.len()].copy_from_slice(&name);
+        }
+        bytes
+    }
+
+    #[test]
+    fn magic_signatures_are_recognized() {
+        let mut pe = vec![0u8; 0x80];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        let mut bmp = vec![0u8; 54];
+        bmp[..2].copy_from_slice(b"BM");
+        bmp[14..18].copy_from_slice(&40u32.to_le_bytes());
+
+        let cases: [(&[u8], &ContentType); 13] = [
+            (b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", &PNG),
+            (&[0xFF, 0xD8, 0xFF, 0xE0], &JPEG),
+            (b"GIF89a\x01\0\x01\0", &GIF),
+            (b"RIFF\0\0\0\0WEBPVP8 ", &WEBP),
+            (b"II*\0\x08\0\0\0", &TIFF),
+            (b"%PDF-1.7\n", &PDF),
+            (&[0x1F, 0x8B, 0x08, 0x00], &GZIP),
+            (b"7z\xBC\xAF\x27\x1C\0\x04", &SEVEN_Z),
+            (b"\x7fELF\x02\x01\x01", &ELF),
+            (&pe, &PE),
+            (&tar, &TAR),
+            (&bmp, &BMP),
+            (&zip_with(&[("notes.txt", b"hello")]), &ZIP),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(sniff_bytes(bytes), Some(expected), "{}", expected.mime);
+        }
+        assert_eq!(sniff_bytes(b"BM is just text here, not a bitmap"), None);
+        assert_eq!(sniff_bytes(b"MZ but no PE header follows"), None);
+        assert_eq!(sniff_bytes(b"plain text"), None);
+        assert_eq!(sniff_bytes(b""), None);
+    }
+
+    #[test]
+    fn ooxml_is_recognized_by_content_types_and_main_part() {
+        let content_types: (&str, &[u8]) = ("[Content_Types].xml", b"<Types/>");
+        let cases: [(&str, &ContentType); 3] =
+            [("xl/workbook.xml", &XLSX), ("word/document.xml", &DOCX), ("ppt/presentation.xml", &PPTX)];
+        for (part, expected) in cases {
+            let bytes = zip_with(&[content_types, ("_rels/.rels", b"<Relationships/>"), (part, b"<x/>")]);
+            assert_eq!(sniff_bytes(&bytes), Some(expected), "{}", part);
+        }
+    }
+
+    #[test]
+    fn zip_mentioning_office_folders_is_plain_zip() {
+        // 没有 [Content_Types].xml 的目录名和只在数据中出现的路径都不算 OOXML
+        let folders = zip_with(&[("xl/", b""), ("word/readme.txt", b"ppt/ notes")]);
+        assert_eq!(sniff_bytes(&folders), Some(&ZIP));
+        let mentions = zip_with(&[("[Content_Types].xml", b"xl/workbook.xml word/document.xml"), ("data.bin", b"")]);
+        assert_eq!(sniff_bytes(&mentions), Some(&ZIP));
+    }
+
+    // 流式写入：每个条目都带数据描述符，本地文件头中没有压缩后的大小
+    fn streamed_zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new_stream(vec![]);
+        for (name, data) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn ooxml_is_found_through_the_central_directory() {
+        let streamed = streamed_zip_with(&[("[Content_Types].xml", b"<Types/>"), ("word/document.xml", b"<x/>")]);
+        assert_ne!(u16::from_le_bytes([streamed[6], streamed[7]]) & 0x08, 0);
+        assert_eq!(sniff_bytes(&streamed), Some(&DOCX));
+
+        // 主文档部件在文件头范围之外
+        let filler: Vec<u8> = (0..HEADER_BYTES * 2).map(|i| (i * 7919 % 251) as u8).collect();
+        let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, data) in [("[Content_Types].xml", &b"<Types/>"[..]), ("docProps/thumbnail.bin", &filler), ("xl/workbook.xml", b"<x/>")] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        assert_eq!(sniff_bytes(&writer.finish().unwrap().into_inner()), Some(&XLSX));
+
+        // 中央目录读不到 (如被截断) 时，有 [Content_Types].xml 即与各 Office 后缀一致
+        let truncated = &streamed[..streamed.len() - 22];
+        let content = sniff_bytes(truncated).unwrap();
+        assert_eq!(content, &OOXML);
+        assert!(content.matches_extension(".xlsx") && content.matches_extension(".pptx"));
+        assert_eq!(sniff_bytes(&streamed_zip_with(&[("notes.txt", b"hello")])), Some(&ZIP));
+    }
+
+    #[test]
+    fn ole2_streams_select_the_office_format() {
+        let cases: [(&str, &ContentType); 5] = [
+            ("Workbook", &XLS),
+            ("Book", &XLS),
+            ("WordDocument", &DOC),
+            ("PowerPoint Document", &PPT),
+            ("__substg1.0_0037001F", &OLE2),
+        ];
+        for (stream, expected) in cases {
+            assert_eq!(sniff_bytes(&ole2_with(&[stream])), Some(expected), "{}", stream);
+        }
+        // 名称只是前缀相同的流不算
+        assert_eq!(sniff_bytes(&ole2_with(&["WorkbookBackup"])), Some(&OLE2));
+        // 目录扇区读不到时只知道是 OLE2
+        assert_eq!(sniff_bytes(&OLE2_MAGIC), Some(&OLE2));
+    }
+
+    #[test]
+    fn renamed_files_are_flagged_as_mismatched() {
+        let dir = test_dir("sniff");
+        let renamed = dir.join("budget.txt");
+        fs::write(&renamed, zip_with(&[("[Content_Types].xml", b""), ("xl/workbook.xml", b"")])).unwrap();
+        let matching = dir.join("budget.xlsx");
+        fs::copy(&renamed, &matching).unwrap();
+        let untyped = dir.join("notes.txt");
+        fs::write(&untyped, "plain text").unwrap();
+
+        let rules = FileRules::default();
+        let class = classify_with(&rules, renamed.to_str().unwrap());
+        assert_eq!(class.content, Some(&XLSX));
+        assert!(class.mismatch());
+        assert_eq!(class.category.as_deref(), Some("EXCEL"));
+        assert!(!classify_with(&rules, matching.to_str().unwrap()).mismatch());
+        // 识别不出内容时不判断是否一致
+        assert!(!classify_with(&rules, untyped.to_str().unwrap()).mismatch());
+
+        // 只知道是 OOXML 的文档按后缀分类，不算不一致
+        let streamed = streamed_zip_with(&[("[Content_Types].xml", b""), ("xl/workbook.xml", b"")]);
+        let sheet = dir.join("report.xlsx");
+        fs::write(&sheet, &streamed[..streamed.len() - 22]).unwrap();
+        let class = classify_with(&rules, sheet.to_str().unwrap());
+        assert_eq!(class.content, Some(&OOXML));
+        assert!(!class.mismatch());
+        assert_eq!(class.category.as_deref(), Some("EXCEL"));
+        let document = dir.join("report.docx");
+        fs::copy(&sheet, &document).unwrap();
+        assert_eq!(classify_with(&rules, document.to_str().unwrap()).category, None);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,281 @@
+// 表格数据解析：把剪贴板中的 CSV / HTML 表格解析为单元格
+// 只做够用的解析，不追求完全符合规范：CSV 支持引号转义，HTML 只识别 tr / td / th
+
+use super::backend::{FORMAT_CSV, FORMAT_CSV_MIME};
+
+// Windows 的 "Csv" 格式使用系统 ANSI 代码页 (如简体中文系统为 GBK)，不是 UTF-8
+#[cfg(windows)]
+fn decode_ansi(bytes: &[u8]) -> String {
+    use windows::Win32::Globalization::{MultiByteToWideChar, CP_ACP, MULTI_BYTE_TO_WIDE_CHAR_FLAGS};
+
+    let flags = MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0);
+    let len = unsafe { MultiByteToWideChar(CP_ACP, flags, bytes, None) };
+    if len <= 0 {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let mut wide = vec![0u16; len as usize];
+    let len = unsafe { MultiByteToWideChar(CP_ACP, flags, bytes, Some(&mut wide)) };
+    String::from_utf16_lossy(&wide[..len.max(0) as usize])
+}
+
+// 其他平台没有 "Csv" 格式，回放 Windows 上的录制时按 UTF-8 处理
+#[cfg(not(windows))]
+fn decode_ansi(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// Windows "HTML Format" 头部中的片段偏移
+fn html_fragment(bytes: &[u8]) -> &[u8] {
+    let header = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]);
+    let offset = |key: &str| -> Option<usize> {
+        let start = header.find(key)? + key.len();
+        header[start..].lines().next()?.trim().parse().ok()
+    };
+    match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start <= end && end <= bytes.len() => &bytes[start..end],
+        _ => bytes,
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').filter(|&end| end <= 10).map(|end| (&rest[1..end], end));
+        let value = entity.and_then(|(name, end)| {
+            let c = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => name
+                    .strip_prefix("#x")
+                    .or_else(|| name.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| name.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match value {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+// 单元格文本：解码实体并合并空白
+fn cell_text(raw: &str) -> String {
+    decode_entities(raw).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 解析 HTML 中的表格，返回按行排列的单元格，没有表格时返回空
+pub fn parse_html_table(html: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Option<Vec<String>> = None;
+    let mut cell: Option<String> = None;
+
+    let finish_cell = |row: &mut Option<Vec<String>>, cell: &mut Option<String>| {
+        if let Some(text) = cell.take() {
+            row.get_or_insert_with(Vec::new).push(cell_text(&text));
+        }
+    };
+
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        if let Some(text) = cell.as_mut() {
+            text.push_str(&rest[..lt]);
+        }
+        rest = &rest[lt..];
+
+        // 注释中可能带有 '>' (如 Excel 的样式表)，整段跳过
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or("");
+            continue;
+        }
+        let Some(gt) = rest.find('>') else { break };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match (closing, name.as_str()) {
+            (false, "td") | (false, "th") => {
+                finish_cell(&mut row, &mut cell);
+                cell = Some(String::new());
+            }
+            (true, "td") | (true, "th") => finish_cell(&mut row, &mut cell),
+            (_, "tr") | (true, "table") => {
+                finish_cell(&mut row, &mut cell);
+                rows.extend(row.take());
+            }
+            (false, "br") | (false, "p") => {
+                if let Some(text) = cell.as_mut() {
+                    text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    finish_cell(&mut row, &mut cell);
+    rows.extend(row.take());
+    rows
+}
+
+// 按第一行中出现最多的分隔符猜测 CSV 分隔符 (部分区域设置下 Excel 使用分号)
+fn detect_delimiter(text: &str) -> char {
+    let first = text.lines().next().unwrap_or_default();
+    // 出现次数相同时优先逗号
+    [',', ';', '\t']
+        .into_iter()
+        .rev()
+        .max_by_key(|d| first.matches(*d).count())
+        .filter(|d| first.contains(*d))
+        .unwrap_or(',')
+}
+
+// 解析 CSV，支持双引号包裹的字段 (字段内可有分隔符、换行和 "" 转义)
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let delimiter = detect_delimiter(text);
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+// 按剪贴板格式解析表格数据
+pub fn parse_table(format: &str, bytes: &[u8]) -> Vec<Vec<String>> {
+    // Windows 的剪贴板数据通常以 NUL 结尾
+    let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    let bytes = &bytes[..end];
+    if format == FORMAT_CSV {
+        parse_csv(&decode_ansi(bytes))
+    } else if format == FORMAT_CSV_MIME {
+        parse_csv(&String::from_utf8_lossy(bytes))
+    } else {
+        parse_html_table(&String::from_utf8_lossy(html_fragment(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::backend::FORMAT_HTML;
+
+    #[test]
+    fn csv_quoted_fields() {
+        let rows = parse_csv("name,note\r\n\"Smith, J\",\"said \"\"hi\"\"\"\r\n");
+        assert_eq!(rows, vec![vec!["name", "note"], vec!["Smith, J", "said \"hi\""]]);
+    }
+
+    #[test]
+    fn csv_embedded_newline() {
+        let rows = parse_csv("a,\"line 1\nline 2\"\nb,c");
+        assert_eq!(rows, vec![vec!["a", "line 1\nline 2"], vec!["b", "c"]]);
+    }
+
+    #[test]
+    fn csv_semicolon_and_tab_delimiters() {
+        assert_eq!(parse_csv("a;b;c\n1;2;3\n"), vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]);
+        assert_eq!(parse_csv("a\tb\n1\t2"), vec![vec!["a", "b"], vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn html_table_cells() {
+        let html = "<table><tr><th>Name</th><th>Qty</th></tr>\
+            <tr><td>A &amp; B</td><td> 1 </td></tr>\
+            <tr><td>x<br>y</td><td>&#x4e2d;</td></tr></table>";
+        assert_eq!(
+            parse_html_table(html),
+            vec![vec!["Name", "Qty"], vec!["A & B", "1"], vec!["x y", "中"]]
+        );
+    }
+
+    #[test]
+    fn html_without_table_is_empty() {
+        assert!(parse_html_table("<p>just a paragraph</p>").is_empty());
+    }
+
+    #[test]
+    fn html_fragment_offsets() {
+        let fragment = "<table><tr><td>in</td></tr></table>";
+        let prefix = "<html><body><table><tr><td>out</td></tr></table><!--StartFragment-->";
+        let header_len = "Version:0.9\r\nStartHTML:0000000000\r\nEndHTML:0000000000\r\n\
+            StartFragment:0000000000\r\nEndFragment:0000000000\r\n"
+            .len();
+        let start = header_len + prefix.len();
+        let end = start + fragment.len();
+        let data = format!(
+            "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n\
+            {}{}<!--EndFragment--></body></html>",
+            header_len,
+            end + 40,
+            start,
+            end,
+            prefix,
+            fragment
+        );
+        assert_eq!(html_fragment(data.as_bytes()), fragment.as_bytes());
+        assert_eq!(parse_table(FORMAT_HTML, data.as_bytes()), vec![vec!["in"]]);
+    }
+
+    #[test]
+    fn html_fragment_invalid_offsets_use_whole_data() {
+        let data = b"StartFragment:500\r\nEndFragment:10\r\n<table><tr><td>a</td></tr></table>";
+        assert_eq!(html_fragment(data), &data[..]);
+    }
+
+    #[test]
+    fn trailing_nul_is_ignored() {
+        assert_eq!(parse_table(FORMAT_CSV_MIME, b"a,b\n\0\0"), vec![vec!["a", "b"]]);
+    }
+}
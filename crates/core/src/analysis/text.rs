@@ -0,0 +1,43 @@
+// 纯文本解析：解码剪贴板中的文本并统计字符、字节和行数
+
+use sha2::{Digest, Sha256};
+
+use super::backend::FORMAT_UNICODETEXT;
+use crate::global::{Selection, TextInfo};
+
+// 解码文本：CF_UNICODETEXT 为 UTF-16LE，其余 (MIME / UTF8_STRING) 按 UTF-8 处理
+// Windows 的剪贴板数据以 NUL 结尾，解码时截掉
+pub fn decode_text(format: &str, bytes: &[u8]) -> String {
+    if format == FORMAT_UNICODETEXT {
+        let chars: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+        String::from_utf16_lossy(&chars)
+    } else {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    }
+}
+
+pub fn text_info(
+    text: &str,
+    preview_chars: usize,
+    format: &str,
+    pid: u32,
+    pname: &str,
+    selection: Selection,
+) -> TextInfo {
+    TextInfo {
+        pname: pname.to_string(),
+        pid,
+        format: format.to_string(),
+        selection,
+        chars: text.chars().count(),
+        bytes: text.len(),
+        lines: text.lines().count(),
+        preview: text.chars().take(preview_chars).collect(),
+        hash: format!("{:x}", Sha256::digest(text.as_bytes())),
+    }
+}
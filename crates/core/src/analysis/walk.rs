@@ -0,0 +1,164 @@
+// 遍历复制的文件夹：按层广度优先，受深度、文件数和时间限制
+// 不跟随目录的符号链接，避免循环
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::global::{MAX_WALK_DEPTH, MAX_WALK_FILES, WALK_TIMEOUT_MS};
+
+// 遍历提前结束的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkLimit {
+    // 有子目录超过了最大深度，没有进入
+    DEPTH,
+    // 遍历的文件数达到上限
+    FILES,
+    // 遍历时间达到上限
+    TIME,
+}
+
+// 一次剪贴板变化中全部文件夹共享的遍历额度
+#[derive(Debug)]
+pub struct WalkBudget {
+    // 复制的文件夹的直接子项深度为 1，0 表示不遍历文件夹
+    pub max_depth: usize,
+    pub files_left: usize,
+    pub deadline: Instant,
+}
+
+impl WalkBudget {
+    // 按当前配置创建
+    pub fn current() -> Self {
+        let timeout = Duration::from_millis(WALK_TIMEOUT_MS.load(Ordering::Relaxed));
+        Self {
+            max_depth: MAX_WALK_DEPTH.load(Ordering::Relaxed),
+            files_left: MAX_WALK_FILES.load(Ordering::Relaxed),
+            deadline: Instant::now() + timeout,
+        }
+    }
+}
+
+// 遍历 root 下的全部文件，对每个文件调用 visit
+// skip 返回 true 的子目录不进入 (如命中排除规则)；返回遍历的文件数和提前结束的原因
+pub fn walk_directory(
+    root: &Path,
+    budget: &mut WalkBudget,
+    skip: impl Fn(&Path) -> bool,
+    mut visit: impl FnMut(&Path),
+) -> (usize, Option<WalkLimit>) {
+    let mut scanned = 0;
+    let mut limit = None;
+    let mut pending: VecDeque<(PathBuf, usize)> = VecDeque::from([(root.to_path_buf(), 1)]);
+
+    while let Some((dir, depth)) = pending.pop_front() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if Instant::now() >= budget.deadline {
+                return (scanned, Some(WalkLimit::TIME));
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            // 指向目录的符号链接不跟随
+            if file_type.is_symlink() && path.is_dir() {
+                continue;
+            }
+            if file_type.is_dir() {
+                if skip(&path) {
+                    continue;
+                }
+                if depth < budget.max_depth {
+                    pending.push_back((path, depth + 1));
+                } else {
+                    limit = Some(WalkLimit::DEPTH);
+                }
+                continue;
+            }
+            if budget.files_left == 0 {
+                return (scanned, Some(WalkLimit::FILES));
+            }
+            budget.files_left -= 1;
+            scanned += 1;
+            visit(&path);
+        }
+    }
+    (scanned, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::utils::test_dir;
+
+    fn budget(max_depth: usize, files: usize) -> WalkBudget {
+        WalkBudget { max_depth, files_left: files, deadline: Instant::now() + Duration::from_secs(60) }
+    }
+
+    // root/a.txt, root/sub/b.txt, root/sub/deep/c.txt
+    fn tree(name: &str) -> PathBuf {
+        let root = test_dir(name);
+        fs::create_dir_all(root.join("sub/deep")).unwrap();
+        for file in ["a.txt", "sub/b.txt", "sub/deep/c.txt"] {
+            fs::write(root.join(file), "x").unwrap();
+        }
+        root
+    }
+
+    fn walk(root: &Path, budget: &mut WalkBudget) -> (Vec<String>, Option<WalkLimit>) {
+        let mut names = vec![];
+        let (scanned, limit) = walk_directory(root, budget, |_| false, |path| {
+            names.push(path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+        });
+        assert_eq!(scanned, names.len());
+        names.sort();
+        (names, limit)
+    }
+
+    #[test]
+    fn depth_budget_skips_deeper_directories() {
+        let root = tree("walk-depth");
+        assert_eq!(walk(&root, &mut budget(3, 100)), (vec!["a.txt".into(), "sub/b.txt".into(), "sub/deep/c.txt".into()], None));
+        assert_eq!(walk(&root, &mut budget(2, 100)), (vec!["a.txt".into(), "sub/b.txt".into()], Some(WalkLimit::DEPTH)));
+        assert_eq!(walk(&root, &mut budget(1, 100)), (vec!["a.txt".into()], Some(WalkLimit::DEPTH)));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn file_budget_is_shared_across_directories() {
+        let root = tree("walk-files");
+        let (names, limit) = walk(&root, &mut budget(3, 2));
+        assert_eq!(names.len(), 2);
+        assert_eq!(limit, Some(WalkLimit::FILES));
+
+        // 一次复制中的多个文件夹共用额度
+        let mut shared = budget(3, 4);
+        assert_eq!(walk(&root, &mut shared).1, None);
+        assert_eq!(shared.files_left, 1);
+        let (names, limit) = walk(&root, &mut shared);
+        assert_eq!(names.len(), 1);
+        assert_eq!(limit, Some(WalkLimit::FILES));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn time_budget_ends_the_walk() {
+        let root = tree("walk-time");
+        let mut expired = WalkBudget { max_depth: 3, files_left: 100, deadline: Instant::now() };
+        assert_eq!(walk(&root, &mut expired), (vec![], Some(WalkLimit::TIME)));
+
+        // visit 中耗尽时间后，下一个条目处结束
+        let mut budget = WalkBudget { max_depth: 3, files_left: 100, deadline: Instant::now() + Duration::from_millis(50) };
+        let (scanned, limit) = walk_directory(&root, &mut budget, |_| false, |_| std::thread::sleep(Duration::from_millis(60)));
+        assert_eq!(scanned, 1);
+        assert_eq!(limit, Some(WalkLimit::TIME));
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::io::{self, PipeReader, Read};
+use std::os::fd::AsFd;
+use std::time::{Duration, Instant};
+
+use rustix::event::{poll, PollFd, PollFlags, Timespec};
+
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+use wayland_client::{event_created_child, Connection, Dispatch, EventQueue, Proxy, QueueHandle};
+use wayland_protocols::ext::data_control::v1::client::{
+    ext_data_control_device_v1::{self, ExtDataControlDeviceV1},
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+    ext_data_control_offer_v1::{self, ExtDataControlOfferV1},
+};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::{self, ZwlrDataControlOfferV1},
+};
+
+use super::backend::{ClipboardBackend, MAX_TRANSFER_BYTES};
+use crate::error::MonitorError;
+use crate::global::Selection;
+use crate::report_error_log;
+use crate::utils::{stop_signal, wait_readable};
+
+// 等待数据源写完管道的超时时间
+const TRANSFER_TIMEOUT_MS: u64 = 1000;
+
+// ext 与 wlr 两套 data-control 协议结构完全一致，统一包装
+enum Offer {
+    Ext(ExtDataControlOfferV1),
+    Wlr(ZwlrDataControlOfferV1),
+}
+
+impl Offer {
+    fn id(&self) -> ObjectId {
+        match self {
+            Offer::Ext(offer) => offer.id(),
+            Offer::Wlr(offer) => offer.id(),
+        }
+    }
+
+    fn receive(&self, mime_type: String, fd: std::os::fd::BorrowedFd) {
+        match self {
+            Offer::Ext(offer) => offer.receive(mime_type, fd),
+            Offer::Wlr(offer) => offer.receive(mime_type, fd),
+        }
+    }
+
+    fn destroy(&self) {
+        match self {
+            Offer::Ext(offer) => offer.destroy(),
+            Offer::Wlr(offer) => offer.destroy(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    // 需要监听的选区
+    watched: Vec<Selection>,
+    // 尚未成为选区的 offer 及其 MIME 类型
+    offers: HashMap<ObjectId, Vec<String>>,
+    // 各选区当前的 offer
+    selections: HashMap<Selection, (Offer, Vec<String>)>,
+    // 尚未处理的变化通知，按到达顺序消费
+    pending: VecDeque<Selection>,
+    finished: bool,
+}
+
+impl State {
+    fn add_mime_type(&mut self, offer: ObjectId, mime_type: String) {
+        self.offers.entry(offer).or_default().push(mime_type);
+    }
+
+    fn set_selection(&mut self, selection: Selection, offer: Option<Offer>) {
+        if !self.watched.contains(&selection) {
+            // 不关心的 offer 直接释放
+            if let Some(offer) = offer {
+                self.offers.remove(&offer.id());
+                offer.destroy();
+            }
+            return;
+        }
+
+        if let Some((old, _)) = self.selections.remove(&selection) {
+            old.destroy();
+        }
+        if let Some(offer) = offer {
+            let mime_types = self.offers.remove(&offer.id()).unwrap_or_default();
+            self.selections.insert(selection, (offer, mime_types));
+        }
+        self.pending.push_back(selection);
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(_: &mut Self, _: &WlRegistry, _: <WlRegistry as Proxy>::Event, _: &GlobalListContents, _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<WlSeat, ()> for State {
+    fn event(_: &mut Self, _: &WlSeat, _: <WlSeat as Proxy>::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<ExtDataControlManagerV1, ()> for State {
+    fn event(_: &mut Self, _: &ExtDataControlManagerV1, _: <ExtDataControlManagerV1 as Proxy>::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<ZwlrDataControlManagerV1, ()> for State {
+    fn event(_: &mut Self, _: &ZwlrDataControlManagerV1, _: <ZwlrDataControlManagerV1 as Proxy>::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<ExtDataControlOfferV1, ()> for State {
+    fn event(state: &mut Self, offer: &ExtDataControlOfferV1, event: ext_data_control_offer_v1::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {
+        if let ext_data_control_offer_v1::Event::Offer { mime_type } = event {
+            state.add_mime_type(offer.id(), mime_type);
+        }
+    }
+}
+
+impl Dispatch<ZwlrDataControlOfferV1, ()> for State {
+    fn event(state: &mut Self, offer: &ZwlrDataControlOfferV1, event: zwlr_data_control_offer_v1::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {
+        if let zwlr_data_control_offer_v1::Event::Offer { mime_type } = event {
+            state.add_mime_type(offer.id(), mime_type);
+        }
+    }
+}
+
+impl Dispatch<ExtDataControlDeviceV1, ()> for State {
+    fn event(state: &mut Self, _: &ExtDataControlDeviceV1, event: ext_data_control_device_v1::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {
+        use ext_data_control_device_v1::Event;
+        match event {
+            Event::DataOffer { id } => {
+                state.offers.insert(id.id(), vec![]);
+            }
+            Event::Selection { id } => state.set_selection(Selection::CLIPBOARD, id.map(Offer::Ext)),
+            Event::PrimarySelection { id } => state.set_selection(Selection::PRIMARY, id.map(Offer::Ext)),
+            Event::Finished => state.finished = true,
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ExtDataControlDeviceV1, [
+        ext_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ExtDataControlOfferV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for State {
+    fn event(state: &mut Self, _: &ZwlrDataControlDeviceV1, event: zwlr_data_control_device_v1::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {
+        use zwlr_data_control_device_v1::Event;
+        match event {
+            Event::DataOffer { id } => {
+                state.offers.insert(id.id(), vec![]);
+            }
+            Event::Selection { id } => state.set_selection(Selection::CLIPBOARD, id.map(Offer::Wlr)),
+            Event::PrimarySelection { id } => state.set_selection(Selection::PRIMARY, id.map(Offer::Wlr)),
+            Event::Finished => state.finished = true,
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+    ]);
+}
+
+// 以非阻塞方式读取管道直到 EOF，整个传输最多等待 TRANSFER_TIMEOUT_MS
+// 超过 MAX_TRANSFER_BYTES 时返回 None
+fn read_transfer(mut reader: PipeReader) -> io::Result<Option<Vec<u8>>> {
+    rustix::io::ioctl_fionbio(&reader, true)?;
+    let deadline = Instant::now() + Duration::from_millis(TRANSFER_TIMEOUT_MS);
+    let mut data = vec![];
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(Some(data)),
+            Ok(n) => {
+                if data.len() + n > MAX_TRANSFER_BYTES {
+                    return Ok(None);
+                }
+                data.extend_from_slice(&buffer[..n]);
+                continue;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let timeout = Timespec::try_from(remaining).map_err(io::Error::other)?;
+        let mut fds = [PollFd::new(&reader, PollFlags::IN)];
+        match poll(&mut fds, Some(&timeout)) {
+            Ok(_) | Err(rustix::io::Errno::INTR) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+// Wayland 剪贴板后端：通过 ext / wlr data-control 协议获取选区 offer 及其 MIME 类型
+// 优先使用 ext_data_control_v1，合成器不支持时退回 zwlr_data_control_manager_v1
+// Wayland 没有 SECONDARY 选区，只支持 CLIPBOARD 和 PRIMARY
+pub struct WaylandBackend {
+    conn: Connection,
+    queue: EventQueue<State>,
+    state: State,
+    // 当前发生变化的选区
+    selection: Selection,
+    stop: PipeReader,
+    // 导致监听结束的连接错误
+    error: Option<MonitorError>,
+}
+
+impl WaylandBackend {
+    pub fn new(selections: &[Selection]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let conn = Connection::connect_to_env()?;
+        let (globals, mut queue) = registry_queue_init::<State>(&conn)?;
+        let qh = queue.handle();
+
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ())?;
+        if let Ok(manager) = globals.bind::<ExtDataControlManagerV1, _, _>(&qh, 1..=1, ()) {
+            manager.get_data_device(&seat, &qh, ());
+        } else {
+            let manager: ZwlrDataControlManagerV1 = globals.bind(&qh, 1..=2, ())
+                .map_err(|_| "compositor supports neither ext_data_control_v1 nor zwlr_data_control_manager_v1")?;
+            manager.get_data_device(&seat, &qh, ());
+        }
+
+        // 建立设备时合成器会立即发送当前选区，这不是一次用户复制，丢弃该通知
+        let mut state = State {
+            watched: selections.to_vec(),
+            ..State::default()
+        };
+        queue.roundtrip(&mut state)?;
+        state.pending.clear();
+
+        Ok(Self {
+            conn,
+            queue,
+            state,
+            selection: Selection::CLIPBOARD,
+            stop: stop_signal()?,
+            error: None,
+        })
+    }
+
+    fn fail(&mut self, message: String) -> bool {
+        report_error_log!("{}", message);
+        self.error = Some(MonitorError::new(message));
+        false
+    }
+}
+
+impl ClipboardBackend for WaylandBackend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        // 没有 offer 表示选区被清空
+        let formats = self.state.selections.get(&self.selection)
+            .map(|(_, mime_types)| mime_types.clone())
+            .unwrap_or_default();
+        Some(formats)
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        let (offer, mime_types) = self.state.selections.get(&self.selection)?;
+        if !mime_types.iter().any(|m| m == format) {
+            return None;
+        }
+
+        let (reader, writer) = std::io::pipe().ok()?;
+        offer.receive(format.to_string(), writer.as_fd());
+        // 关闭本端的写端，数据源写完并关闭后读端才能读到 EOF
+        drop(writer);
+        self.conn.flush().ok()?;
+
+        // 返回时读端随 reader 关闭，无响应或写入过多的数据源会收到 EPIPE
+        match read_transfer(reader) {
+            Ok(Some(data)) => Some(data),
+            Ok(None) => {
+                report_error_log!("Wayland transfer of {} exceeds {} bytes, dropped.", format, MAX_TRANSFER_BYTES);
+                None
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                report_error_log!("Wayland transfer of {} timed out.", format);
+                None
+            }
+            Err(e) => {
+                report_error_log!("Wayland transfer of {} failed: {}", format, e);
+                None
+            }
+        }
+    }
+
+    // data-control 协议不提供数据源的进程信息
+    fn owner(&mut self) -> Option<(u32, String)> {
+        None
+    }
+
+    fn knows_owner(&self) -> bool {
+        false
+    }
+
+    fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        loop {
+            if let Some(selection) = self.state.pending.pop_front() {
+                self.selection = selection;
+                return true;
+            }
+            if self.state.finished {
+                return self.fail("Wayland data-control device was finished by the compositor".to_string());
+            }
+            if let Err(e) = self.queue.dispatch_pending(&mut self.state) {
+                return self.fail(format!("Wayland connection error: {}", e));
+            }
+            if !self.state.pending.is_empty() || self.state.finished {
+                continue;
+            }
+            // 与 blocking_dispatch 相同，但同时等待停止信号
+            if let Err(e) = self.conn.flush() {
+                return self.fail(format!("Wayland connection error: {}", e));
+            }
+            let Some(guard) = self.queue.prepare_read() else {
+                continue;
+            };
+            if !wait_readable(guard.connection_fd(), &self.stop) {
+                return false;
+            }
+            if let Err(e) = guard.read() {
+                return self.fail(format!("Wayland connection error: {}", e));
+            }
+        }
+    }
+
+    fn take_error(&mut self) -> Option<MonitorError> {
+        self.error.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn transfer_is_read_until_eof() {
+        let (reader, mut writer) = std::io::pipe().unwrap();
+        let source = thread::spawn(move || {
+            writer.write_all(b"first ").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            writer.write_all(b"second").unwrap();
+        });
+        assert_eq!(read_transfer(reader).unwrap(), Some(b"first second".to_vec()));
+        source.join().unwrap();
+    }
+
+    #[test]
+    fn unresponsive_source_times_out() {
+        let (reader, mut writer) = std::io::pipe().unwrap();
+        writer.write_all(b"partial").unwrap();
+        let started = Instant::now();
+        let error = read_transfer(reader).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() >= Duration::from_millis(TRANSFER_TIMEOUT_MS));
+        // 读端已关闭，数据源继续写入会失败而不是一直阻塞
+        assert_eq!(writer.write_all(b"more").unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+    }
+}
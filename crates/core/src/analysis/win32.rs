@@ -0,0 +1,172 @@
+use std::cell::Cell;
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+
+use windows::{
+    core::{ Error, PCWSTR, Result },
+    Win32::Foundation::{HGLOBAL, HWND},
+    Win32::System::DataExchange::*,
+    Win32::System::Ole::*,
+    Win32::System::Memory::{GlobalLock, GlobalUnlock, GlobalSize},
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use super::backend::{
+    ClipboardBackend, FORMAT_BITMAP, FORMAT_DIB, FORMAT_HDROP, FORMAT_UNICODETEXT,
+};
+use crate::error::MonitorError;
+use crate::hooks::create_monitor_window;
+use crate::report_error_log;
+use crate::utils::{to_wstring, get_process_info};
+
+thread_local! {
+    // wnd_proc 收到 WM_CLIPBOARDUPDATE 时置位，由 wait_for_change 消费
+    pub(crate) static CLIPBOARD_CHANGED: Cell<bool> = const { Cell::new(false) };
+}
+
+// 标准格式 ID 与名称的对应关系
+const STANDARD_FORMATS: [(CLIPBOARD_FORMAT, &str); 4] = [
+    (CF_HDROP, FORMAT_HDROP),
+    (CF_BITMAP, FORMAT_BITMAP),
+    (CF_DIB, FORMAT_DIB),
+    (CF_UNICODETEXT, FORMAT_UNICODETEXT),
+];
+
+fn format_id(name: &str) -> u32 {
+    if let Some((id, _)) = STANDARD_FORMATS.iter().find(|(_, n)| *n == name) {
+        return id.0 as u32;
+    }
+    // 注册自定义格式 (只需注册一次，系统会返回相同的ID)
+    unsafe { RegisterClipboardFormatW(PCWSTR(to_wstring(name).as_ptr())) }
+}
+
+fn format_name(id: u32) -> String {
+    if let Some((_, name)) = STANDARD_FORMATS.iter().find(|(f, _)| f.0 as u32 == id) {
+        return name.to_string();
+    }
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClipboardFormatNameW(id, &mut buffer) };
+    if len > 0 {
+        String::from_utf16_lossy(&buffer[..len as usize])
+    } else {
+        format!("#{}", id)
+    }
+}
+
+// 打开剪贴板，其他程序正占用时短暂重试，避免把打开失败误判为剪贴板被清空
+fn open_clipboard() -> bool {
+    for _ in 0..5 {
+        if unsafe { OpenClipboard(None).is_ok() } {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+// Win32 剪贴板后端：通过消息窗口接收 WM_CLIPBOARDUPDATE
+pub struct Win32Backend {
+    hwnd: HWND,
+    // GetMessageW 失败时的错误
+    error: Option<MonitorError>,
+}
+
+impl Win32Backend {
+    pub fn new() -> Result<Self> {
+        let hwnd = unsafe { create_monitor_window()? };
+        // 在这里而不是 WM_CREATE 中注册监听，失败时才能拿到真实的错误码
+        if let Err(e) = unsafe { AddClipboardFormatListener(hwnd) } {
+            report_error_log!("Failed to add clipboard format listener.");
+            let _ = unsafe { DestroyWindow(hwnd) };
+            return Err(e);
+        }
+        Ok(Self { hwnd, error: None })
+    }
+}
+
+impl Drop for Win32Backend {
+    fn drop(&mut self) {
+        // WM_DESTROY 中注销剪贴板监听
+        let _ = unsafe { DestroyWindow(self.hwnd) };
+    }
+}
+
+impl ClipboardBackend for Win32Backend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        // 重试后仍打不开时无法区分剪贴板是否为空，返回 None 跳过这次变化
+        if !open_clipboard() {
+            report_error_log!("Failed to open the clipboard, change skipped.");
+            return None;
+        }
+        let mut formats = vec![];
+        let mut id = unsafe { EnumClipboardFormats(0) };
+        while id != 0 {
+            formats.push(format_name(id));
+            id = unsafe { EnumClipboardFormats(id) };
+        }
+        let _ = unsafe { CloseClipboard() };
+        Some(formats)
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        let id = format_id(format);
+        if !open_clipboard() {
+            return None;
+        }
+
+        let mut data: Option<Vec<u8>> = None;
+        if let Ok(handle) = unsafe { GetClipboardData(id) } {
+            let h_mem = HGLOBAL(handle.0 as _);
+            let ptr = unsafe { GlobalLock(h_mem) };
+            if !ptr.is_null() {
+                let size = unsafe { GlobalSize(h_mem) };
+                let mut buffer = vec![0u8; size];
+                unsafe {
+                    ptr::copy_nonoverlapping(ptr as *const u8, buffer.as_mut_ptr(), size);
+                }
+                data = Some(buffer);
+                let _unlock = unsafe { GlobalUnlock(h_mem) };
+            }
+        }
+        let _close = unsafe { CloseClipboard() };
+        data
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        let hwnd = unsafe { GetClipboardOwner() }.ok()?;
+        unsafe { get_process_info(hwnd) }.ok()
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        let mut msg = MSG::default();
+        loop {
+            match unsafe { GetMessageW(&mut msg, None, 0, 0) }.0 {
+                // 收到 WM_QUIT
+                0 => return false,
+                -1 => {
+                    let e = Error::from_thread();
+                    report_error_log!("GetMessageW failed: {}", e);
+                    self.error = Some(e.into());
+                    return false;
+                }
+                _ => {}
+            }
+            unsafe {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+            if CLIPBOARD_CHANGED.with(|changed| changed.replace(false)) {
+                return true;
+            }
+        }
+    }
+
+    fn take_error(&mut self) -> Option<MonitorError> {
+        self.error.take()
+    }
+
+    fn has_format(&mut self, format: &str) -> bool {
+        unsafe { IsClipboardFormatAvailable(format_id(format)).is_ok() }
+    }
+}
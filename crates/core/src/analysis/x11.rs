@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::PipeReader;
+use std::os::fd::AsFd;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11rb::connection::Connection;
+use x11rb::protocol::res::{ClientIdMask, ClientIdSpec, ConnectionExt as _};
+use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, Property, Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::{CURRENT_TIME, NONE};
+
+use super::backend::{ClipboardBackend, MAX_TRANSFER_BYTES};
+use crate::error::MonitorError;
+use crate::global::Selection;
+use crate::report_error_log;
+use crate::utils::{get_process_name, stop_signal, wait_readable};
+
+// 等待选区所有者响应转换请求的超时时间
+const TRANSFER_TIMEOUT_MS: u64 = 1000;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        CLIPBOARD,
+        PRIMARY,
+        SECONDARY,
+        TARGETS,
+        INCR,
+        _NET_WM_PID,
+        // 接收转换结果用的属性
+        CLIPBOARD_COPY_AWARE,
+    }
+}
+
+fn selection_atom(atoms: &Atoms, selection: Selection) -> Atom {
+    match selection {
+        Selection::CLIPBOARD => atoms.CLIPBOARD,
+        Selection::PRIMARY => atoms.PRIMARY,
+        Selection::SECONDARY => atoms.SECONDARY,
+    }
+}
+
+// X11 剪贴板后端：通过 XFixes 订阅选区 (CLIPBOARD / PRIMARY / SECONDARY) 所有者变化，
+// 通过 TARGETS 列出格式
+pub struct X11Backend {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+    // 当前发生变化的选区
+    selection: Selection,
+    // 当前选区所有者窗口，NONE 表示选区已被清空
+    owner: Window,
+    // 尚未处理的变化通知 (选区, 所有者)，按到达顺序消费
+    pending: VecDeque<(Selection, Window)>,
+    // 当前选区的格式列表缓存，每次变化后失效
+    targets: Option<Vec<String>>,
+    stop: PipeReader,
+    // 导致监听结束的连接错误
+    error: Option<MonitorError>,
+}
+
+impl X11Backend {
+    pub fn new(selections: &[Selection]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = &conn.setup().roots[screen_num];
+
+        // 不映射的窗口，仅用于接收选区数据和事件
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0, 0, 1, 1, 0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        conn.xfixes_query_version(5, 0)?.reply()?;
+        for selection in selections {
+            conn.xfixes_select_selection_input(
+                window,
+                selection_atom(&atoms, *selection),
+                SelectionEventMask::SET_SELECTION_OWNER
+                    | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )?;
+        }
+        // X-Resource 扩展用于查询所有者 pid，不可用时退回 _NET_WM_PID
+        if let Ok(cookie) = conn.res_query_version(1, 2) {
+            let _ = cookie.reply();
+        }
+        // 记录启动时的 CLIPBOARD 所有者，便于不等待变化直接查看当前内容
+        let owner = conn.get_selection_owner(atoms.CLIPBOARD)?.reply()?.owner;
+        conn.flush()?;
+
+        Ok(Self {
+            conn,
+            window,
+            atoms,
+            selection: Selection::CLIPBOARD,
+            owner,
+            pending: VecDeque::new(),
+            targets: None,
+            stop: stop_signal()?,
+            error: None,
+        })
+    }
+
+    // 记录与当前请求无关的事件，避免丢失选区变化通知
+    fn note_event(&mut self, event: Event) {
+        if let Event::XfixesSelectionNotify(ev) = event {
+            let selection = [Selection::CLIPBOARD, Selection::PRIMARY, Selection::SECONDARY]
+                .into_iter()
+                .find(|s| selection_atom(&self.atoms, *s) == ev.selection);
+            if let Some(selection) = selection {
+                self.pending.push_back((selection, ev.owner));
+            }
+        }
+    }
+
+    // 在超时时间内等待满足条件的事件
+    fn wait_for(&mut self, matches: impl Fn(&Event) -> bool) -> Option<Event> {
+        let deadline = Instant::now() + Duration::from_millis(TRANSFER_TIMEOUT_MS);
+        while Instant::now() < deadline {
+            match self.conn.poll_for_event() {
+                Ok(Some(event)) if matches(&event) => return Some(event),
+                Ok(Some(event)) => self.note_event(event),
+                Ok(None) => thread::sleep(Duration::from_millis(5)),
+                Err(e) => {
+                    report_error_log!("X11 connection error: {}", e);
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    // 请求选区所有者把内容转换为 target 并写入我们窗口的属性
+    fn convert(&mut self, target: Atom) -> Option<Vec<u8>> {
+        let (window, property) = (self.window, self.atoms.CLIPBOARD_COPY_AWARE);
+        let selection = selection_atom(&self.atoms, self.selection);
+        self.conn
+            .convert_selection(window, selection, target, property, CURRENT_TIME)
+            .ok()?;
+        self.conn.flush().ok()?;
+
+        let event = self.wait_for(|e| matches!(e, Event::SelectionNotify(ev) if ev.requestor == window))?;
+        let Event::SelectionNotify(notify) = event else {
+            return None;
+        };
+        // 所有者拒绝转换
+        if notify.property == NONE {
+            return None;
+        }
+
+        let reply = self.conn
+            .get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX)
+            .ok()?
+            .reply()
+            .ok()?;
+        if reply.type_ == self.atoms.INCR {
+            return self.read_incr();
+        }
+        Some(reply.value)
+    }
+
+    // INCR 分块传输：删除属性后所有者逐块写入，长度为 0 的块表示结束
+    fn read_incr(&mut self) -> Option<Vec<u8>> {
+        let (window, property) = (self.window, self.atoms.CLIPBOARD_COPY_AWARE);
+        let mut data = vec![];
+        loop {
+            self.wait_for(|e| {
+                matches!(e, Event::PropertyNotify(ev)
+                    if ev.window == window && ev.atom == property && ev.state == Property::NEW_VALUE)
+            })?;
+            let reply = self.conn
+                .get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX)
+                .ok()?
+                .reply()
+                .ok()?;
+            if reply.value.is_empty() {
+                return Some(data);
+            }
+            data.extend_from_slice(&reply.value);
+            if data.len() > MAX_TRANSFER_BYTES {
+                report_error_log!("X11 transfer exceeds {} bytes, dropped.", MAX_TRANSFER_BYTES);
+                return None;
+            }
+        }
+    }
+
+    fn owner_pid(&self) -> Option<u32> {
+        let spec = ClientIdSpec {
+            client: self.owner,
+            mask: ClientIdMask::LOCAL_CLIENT_PID,
+        };
+        let from_res = self.conn
+            .res_query_client_ids(&[spec])
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| {
+                reply.ids.into_iter()
+                    .find(|id| id.spec.mask == ClientIdMask::LOCAL_CLIENT_PID)
+                    .and_then(|id| id.value.first().copied())
+            });
+        if from_res.is_some() {
+            return from_res;
+        }
+
+        self.conn
+            .get_property(false, self.owner, self.atoms._NET_WM_PID, AtomEnum::CARDINAL, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()
+    }
+}
+
+impl ClipboardBackend for X11Backend {
+    fn formats(&mut self) -> Option<Vec<String>> {
+        if let Some(targets) = &self.targets {
+            return Some(targets.clone());
+        }
+        // 选区没有所有者表示被清空
+        if self.owner == NONE {
+            return Some(vec![]);
+        }
+
+        // 所有者不响应 TARGETS 时无法得知内容，跳过这次变化
+        let Some(bytes) = self.convert(self.atoms.TARGETS) else {
+            report_error_log!("Selection owner did not answer TARGETS, change skipped.");
+            return None;
+        };
+        let atoms: Vec<Atom> = bytes.chunks_exact(4)
+            .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+
+        let targets: Vec<String> = atoms.into_iter()
+            .filter_map(|atom| self.conn.get_atom_name(atom).ok()?.reply().ok())
+            .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+            .collect();
+        self.targets = Some(targets.clone());
+        Some(targets)
+    }
+
+    fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+        let atom = self.conn.intern_atom(false, format.as_bytes()).ok()?.reply().ok()?.atom;
+        self.convert(atom)
+    }
+
+    fn owner(&mut self) -> Option<(u32, String)> {
+        if self.owner == NONE {
+            return None;
+        }
+        let pid = self.owner_pid()?;
+        let pname = get_process_name(pid).unwrap_or_else(|| "Unknown".to_string());
+        Some((pid, pname))
+    }
+
+    fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    fn wait_for_change(&mut self) -> bool {
+        loop {
+            if let Some((selection, owner)) = self.pending.pop_front() {
+                self.selection = selection;
+                self.owner = owner;
+                self.targets = None;
+                return true;
+            }
+            // 先取已缓冲的事件，没有时再等待连接可读或停止信号
+            match self.conn.poll_for_event() {
+                Ok(Some(event)) => self.note_event(event),
+                Ok(None) => {
+                    if !wait_readable(self.conn.stream().as_fd(), &self.stop) {
+                        return false;
+                    }
+                }
+                Err(e) => {
+                    report_error_log!("X11 connection error: {}", e);
+                    self.error = Some(MonitorError::new(format!("X11 connection error: {}", e)));
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn take_error(&mut self) -> Option<MonitorError> {
+        self.error.take()
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+
+// 监听启动或运行失败的错误，code 为系统错误码 (Windows HRESULT 等)，没有时为 0
+#[derive(Debug, Clone)]
+pub struct MonitorError {
+    pub message: String,
+    pub code: i32,
+}
+
+impl MonitorError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), code: 0 }
+    }
+}
+
+impl fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (Code: 0x{:X})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for MonitorError {}
+
+#[cfg(windows)]
+impl From<windows::core::Error> for MonitorError {
+    fn from(e: windows::core::Error) -> Self {
+        Self { message: e.message(), code: e.code().0 }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for MonitorError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::new(e.to_string())
+    }
+}
@@ -0,0 +1,484 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock, RwLock};
+use std::time::Instant;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use crate::analysis::archive::{ArchiveFormat, ArchiveLimit};
+use crate::analysis::code::CodeLanguage;
+use crate::analysis::pattern::PathRules;
+use crate::analysis::sniff::{self, ContentType};
+use crate::analysis::walk::WalkLimit;
+use crate::error::MonitorError;
+
+// 使用 Mutex 记录上一次打印的时间
+// 注意：这里使用 Mutex 是因为我们需要在 immutable 的静态上下文中修改时间
+pub static LAST_UPDATE_LOG: Mutex<Option<Instant>> = Mutex::new(None);
+// PRIMARY / SECONDARY 选区单独节流，避免划词选中挤掉紧随其后的复制
+pub static LAST_PRIMARY_UPDATE: Mutex<Option<Instant>> = Mutex::new(None);
+pub static LAST_SECONDARY_UPDATE: Mutex<Option<Instant>> = Mutex::new(None);
+// 要监控的文件分类，运行中可以整体替换
+static FILE_RULES: LazyLock<RwLock<Arc<FileRules>>> = LazyLock::new(Default::default);
+// 来源路径的包含 / 排除规则，运行中可以整体替换
+static PATH_RULES: LazyLock<RwLock<Arc<PathRules>>> = LazyLock::new(Default::default);
+
+// 日志输出目标，由宿主 (如 napi 绑定) 注册
+pub type LogSink = Box<dyn Fn(String) + Send + Sync>;
+static LOG_SINK: OnceLock<LogSink> = OnceLock::new();
+
+// 用于记录后台监控线程的 ID，Windows 上用于发送 WM_QUIT
+pub static MONITOR_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+// 事件序号，每上报一个事件加一，JS 侧可据此发现丢失的事件
+pub static EVENT_SEQ: AtomicU64 = AtomicU64::new(0);
+// 表格事件中最多携带的单元格数量
+pub static MAX_TABLE_CELLS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_TABLE_CELLS);
+// 文本事件预览的最大字符数，0 表示不带预览
+pub static TEXT_PREVIEW_CHARS: AtomicUsize = AtomicUsize::new(DEFAULT_TEXT_PREVIEW_CHARS);
+// 是否识别复制的代码片段，识别出的文本按代码事件上报
+pub static DETECT_CODE: AtomicBool = AtomicBool::new(true);
+// 遍历复制的文件夹的最大深度，0 表示不遍历文件夹
+pub static MAX_WALK_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_WALK_DEPTH);
+// 一次剪贴板变化中最多遍历的文件数 (全部文件夹合计)
+pub static MAX_WALK_FILES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_WALK_FILES);
+// 一次剪贴板变化中遍历文件夹的最长时间 (毫秒)
+pub static WALK_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_WALK_TIMEOUT_MS);
+// 列出复制的压缩包时的最大嵌套深度，0 表示不列出压缩包
+pub static MAX_ARCHIVE_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ARCHIVE_DEPTH);
+// 一次剪贴板变化中最多列出的压缩包条目数 (全部压缩包合计)
+pub static MAX_ARCHIVE_ENTRIES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ARCHIVE_ENTRIES);
+// 一次剪贴板变化中最多解压的字节数 (全部压缩包合计)，嵌套的压缩包在这个范围内读入内存
+pub static MAX_ARCHIVE_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_ARCHIVE_BYTES);
+// 没有匹配任何分类的文件也上报，归入 OTHER_CATEGORY
+pub static REPORT_UNMATCHED: AtomicBool = AtomicBool::new(false);
+// 是否为复制的文件计算 SHA-256 / BLAKE3，默认都不计算
+pub static HASH_SHA256: AtomicBool = AtomicBool::new(false);
+pub static HASH_BLAKE3: AtomicBool = AtomicBool::new(false);
+// 超过这个大小的文件不计算哈希
+pub static MAX_HASH_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_HASH_BYTES);
+// 用于记录上一次图片的字节大小，用于过滤重复事件
+pub static LAST_IMG_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+// === 节流控制配置 ===
+// 定义节流时间阈值：500毫秒
+pub const THROTTLE_MS: u64 = 500;
+pub const DEFAULT_MAX_TABLE_CELLS: usize = 1000;
+pub const DEFAULT_TEXT_PREVIEW_CHARS: usize = 100;
+pub const DEFAULT_MAX_WALK_DEPTH: usize = 16;
+pub const DEFAULT_MAX_WALK_FILES: usize = 10_000;
+pub const DEFAULT_WALK_TIMEOUT_MS: u64 = 2000;
+pub const DEFAULT_MAX_ARCHIVE_DEPTH: usize = 3;
+pub const DEFAULT_MAX_ARCHIVE_ENTRIES: usize = 10_000;
+pub const DEFAULT_MAX_ARCHIVE_BYTES: u64 = 64 * 1024 * 1024;
+pub const DEFAULT_MAX_HASH_BYTES: u64 = 256 * 1024 * 1024;
+// 开启 REPORT_UNMATCHED 时没有匹配分类的文件使用的分类名称
+pub const OTHER_CATEGORY: &str = "OTHER";
+
+// 事件来源的选区：Windows 只有 CLIPBOARD，X11 还有 PRIMARY (划词) 和 SECONDARY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Selection {
+    CLIPBOARD,
+    PRIMARY,
+    SECONDARY
+}
+
+// 文件的元数据，读取不到的项为 None
+#[derive(Debug, Clone, Default)]
+pub struct FileMetadata {
+    // 字节数；压缩包中的条目为解压后的大小
+    pub size: Option<u64>,
+    // 修改时间和创建时间 (Unix 毫秒)，部分文件系统没有创建时间
+    pub modified_at: Option<u64>,
+    pub created_at: Option<u64>,
+    // 所有者 uid 和权限位 (如 0o644)，只有 Unix 有
+    pub uid: Option<u32>,
+    pub permissions: Option<u32>,
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    pub name: String,
+    pub path: String,
+    // 匹配到的分类名称
+    pub category: String,
+    // 声明的后缀 (带点号、小写)，没有后缀时为空串
+    pub extension: String,
+    // 按文件头识别出的 MIME 类型，无法识别时为 None
+    pub mime_type: Option<String>,
+    // 识别出的内容与后缀不一致 (如改名为 .txt 的表格)
+    pub extension_mismatch: bool,
+    // 在复制的文件夹中找到时为该文件夹的路径
+    pub directory: Option<String>,
+    // 在压缩包中找到时为条目在压缩包中的路径，此时 path 是压缩包的路径
+    pub archive_entry: Option<String>,
+    pub metadata: FileMetadata,
+    pub pname: String,
+    pub pid: u32,
+    pub selection: Selection,
+}
+
+// 复制的文件夹的遍历结果
+#[derive(Debug, Clone)]
+pub struct DirectoryInfo {
+    pub path: String,
+    // 遍历到的文件数
+    pub files: usize,
+    // 每个分类匹配到的文件数
+    pub categories: BTreeMap<String, usize>,
+    // 遍历因达到限制没有完成
+    pub limit: Option<WalkLimit>,
+}
+
+// 复制的压缩包 (包括文件夹中找到的) 的列出结果
+#[derive(Debug, Clone)]
+pub struct ArchiveInfo {
+    pub path: String,
+    pub format: ArchiveFormat,
+    // 列出的文件数，包括嵌套压缩包中的
+    pub entries: usize,
+    // 每个分类匹配到的条目数
+    pub categories: BTreeMap<String, usize>,
+    // 列出因达到限制没有完成
+    pub limit: Option<ArchiveLimit>,
+    // 压缩包损坏或格式不支持时的错误
+    pub error: Option<String>,
+}
+
+// 复制的文件：匹配分类的文件 (包括文件夹和压缩包中找到的)，以及每个文件夹和压缩包的统计
+#[derive(Debug, Clone, Default)]
+pub struct FilesInfo {
+    pub files: Vec<FileInfo>,
+    pub directories: Vec<DirectoryInfo>,
+    pub archives: Vec<ArchiveInfo>,
+}
+
+// 一个文件的内容哈希 (十六进制)，没有开启的算法为 None
+#[derive(Debug, Clone)]
+pub struct FileHash {
+    pub path: String,
+    pub category: String,
+    pub sha256: Option<String>,
+    pub blake3: Option<String>,
+}
+
+// 文件事件的哈希：文件事件先投递，哈希由后台线程算完后作为后续事件上报
+// 压缩包中的条目、超过 MAX_HASH_BYTES 和读取失败的文件不包括在内
+#[derive(Debug, Clone)]
+pub struct HashesInfo {
+    // 对应的文件事件的序号
+    pub event_seq: u64,
+    pub files: Vec<FileHash>,
+}
+
+// 一个分类的文件数和字节数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategorySummary {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+// 上报的文件的汇总，不包括压缩包中的条目 (见 ArchiveInfo)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilesSummary {
+    pub files: usize,
+    pub bytes: u64,
+    pub categories: BTreeMap<String, CategorySummary>,
+}
+
+impl FilesInfo {
+    // 有文件夹没有遍历完或压缩包没有列完，文件列表和统计都不完整
+    pub fn truncated(&self) -> bool {
+        self.directories.iter().any(|d| d.limit.is_some()) || self.archives.iter().any(|a| a.limit.is_some())
+    }
+
+    // 按分类汇总文件数和字节数，读取不到大小的文件按 0 字节计
+    pub fn summary(&self) -> FilesSummary {
+        let mut summary = FilesSummary::default();
+        for file in self.files.iter().filter(|file| file.archive_entry.is_none()) {
+            let bytes = file.metadata.size.unwrap_or(0);
+            summary.files += 1;
+            summary.bytes += bytes;
+            let category = summary.categories.entry(file.category.clone()).or_default();
+            category.files += 1;
+            category.bytes += bytes;
+        }
+        summary
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShotInfo {
+    pub pname: String,
+    pub pid: u32,
+    // 图片数据
+    pub data: Vec<u8>,
+    pub selection: Selection,
+}
+
+// 复制的表格数据 (HTML / CSV)
+#[derive(Debug, Clone)]
+pub struct TableInfo {
+    pub pname: String,
+    pub pid: u32,
+    // 数据来源的剪贴板格式
+    pub format: String,
+    pub selection: Selection,
+    pub rows: usize,
+    // 最宽一行的列数
+    pub columns: usize,
+    // 按行排列的单元格，超过 MAX_TABLE_CELLS 的部分被截掉
+    pub cells: Vec<Vec<String>>,
+    pub truncated: bool,
+}
+
+// 复制的纯文本
+#[derive(Debug, Clone)]
+pub struct TextInfo {
+    pub pname: String,
+    pub pid: u32,
+    // 数据来源的剪贴板格式
+    pub format: String,
+    pub selection: Selection,
+    pub chars: usize,
+    // UTF-8 编码后的字节数
+    pub bytes: usize,
+    pub lines: usize,
+    // 开头最多 TEXT_PREVIEW_CHARS 个字符
+    pub preview: String,
+    // 文本 UTF-8 内容的 SHA-256 (十六进制)
+    pub hash: String,
+}
+
+// 复制的代码片段：按文本识别出的编程语言
+#[derive(Debug, Clone)]
+pub struct CodeInfo {
+    pub text: TextInfo,
+    pub language: CodeLanguage,
+    // 0 ~ 1，只是启发式的估计
+    pub confidence: f64,
+}
+
+// 每个选区各自的节流计时器
+pub fn throttle_timer(selection: Selection) -> &'static Mutex<Option<Instant>> {
+    match selection {
+        Selection::CLIPBOARD => &LAST_UPDATE_LOG,
+        Selection::PRIMARY => &LAST_PRIMARY_UPDATE,
+        Selection::SECONDARY => &LAST_SECONDARY_UPDATE,
+    }
+}
+
+// 注册日志输出目标，只能注册一次
+pub fn set_log_sink(sink: LogSink) -> Result<(), LogSink> {
+    LOG_SINK.set(sink)
+}
+
+fn report_log(msg: String) {
+    if let Some(sink) = LOG_SINK.get() {
+        sink(msg);
+    } else if cfg!(debug_assertions) {
+        println!("{}", msg);
+    } else {
+        println!("Warning: No report log listener registered yet!");
+    }
+}
+
+#[doc(hidden)]
+pub fn report_error(msg: fmt::Arguments) {
+  let log_msg = format!("[clipboard_error]: {}", msg);
+  report_log(log_msg);
+}
+
+#[doc(hidden)]
+pub fn report_info(msg: fmt::Arguments) {
+  let log_msg = format!("[clipboard_info]: {}", msg);
+  report_log(log_msg);
+}
+
+#[macro_export]
+macro_rules! report_error_log {
+    // format_args! 是编译器内置宏，它不分配内存，只打包参数
+    ($($arg:tt)*) => {
+        $crate::global::report_error(format_args!($($arg)*))
+    }
+}
+
+#[macro_export]
+macro_rules! report_info_log {
+    // format_args! 是编译器内置宏，它不分配内存，只打包参数
+    ($($arg:tt)*) => {
+        $crate::global::report_info(format_args!($($arg)*))
+    }
+}
+
+// 一个文件分类：按后缀或文件头识别出的 MIME 类型匹配
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCategory {
+    pub name: String,
+    // 后缀 (带点号、小写)
+    pub extensions: HashSet<String>,
+    // MIME 类型 (小写)，支持 "image/*" 形式的通配
+    pub mime_types: HashSet<String>,
+    // 多个分类都匹配时取优先级最高的，相同时取先定义的
+    pub priority: i32,
+}
+
+impl FileCategory {
+    // 校验并规范化用户传入的分类
+    pub fn new(
+        name: &str,
+        extensions: &[String],
+        mime_types: &[String],
+        priority: i32,
+    ) -> Result<Self, MonitorError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(MonitorError::new("File category name must not be empty"));
+        }
+        let extensions = normalize_extensions(extensions)
+            .map_err(|e| MonitorError::new(format!("File category {:?}: {}", name, e.message)))?;
+        let mime_types = normalize_mime_types(mime_types)
+            .map_err(|e| MonitorError::new(format!("File category {:?}: {}", name, e.message)))?;
+        Ok(Self { name: name.to_string(), extensions, mime_types, priority })
+    }
+
+    fn matches_mime(&self, mime: &str) -> bool {
+        self.mime_types.iter().any(|pattern| match pattern.strip_suffix("/*") {
+            Some(major) => mime.split('/').next() == Some(major),
+            None => pattern == mime,
+        })
+    }
+
+    // 按识别出的内容匹配：MIME 类型，或该内容常见的后缀
+    fn matches_content(&self, content: &ContentType) -> bool {
+        self.matches_mime(content.mime) || content.extensions.iter().any(|ext| self.extensions.contains(*ext))
+    }
+}
+
+// 全部文件分类，按优先级从高到低排列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRules {
+    categories: Vec<FileCategory>,
+}
+
+impl Default for FileRules {
+    fn default() -> Self {
+        let set = |items: &[&str]| items.iter().map(|e| e.to_string()).collect();
+        let category = |name: &str, extensions: &[&str], mime_types: &[&str], priority| FileCategory {
+            name: name.to_string(),
+            extensions: set(extensions),
+            mime_types: set(mime_types),
+            priority,
+        };
+        Self {
+            categories: vec![
+                category("IMAGE", &[".jpg", ".jpeg", ".png", ".bmp", ".gif", ".ico", ".tiff", ".webp"], &["image/*"], 2),
+                category("EXCEL", &[".xls", ".xlsx", ".csv", ".xlsm"], &[], 1),
+                // 代码文件的优先级较低
+                category("CODE", &[
+                    ".cpp", ".h", ".hpp", ".c", ".cs", ".py", ".java", ".js", ".ts",
+                    ".html", ".css", ".json", ".xml", ".sql", ".go", ".rs",
+                ], &[], 0),
+            ],
+        }
+    }
+}
+
+impl FileRules {
+    // 分类名称不能重复 (不区分大小写)
+    pub fn new(mut categories: Vec<FileCategory>) -> Result<Self, MonitorError> {
+        let mut names = HashSet::new();
+        for category in &categories {
+            if !names.insert(category.name.to_lowercase()) {
+                return Err(MonitorError::new(format!("Duplicate file category {:?}", category.name)));
+            }
+        }
+        // 稳定排序，优先级相同时保持定义顺序
+        categories.sort_by_key(|category| std::cmp::Reverse(category.priority));
+        Ok(Self { categories })
+    }
+
+    pub fn categories(&self) -> &[FileCategory] {
+        &self.categories
+    }
+
+    // 识别出内容时先按内容匹配，没有分类匹配内容时再按声明的后缀 (带点号、小写) 匹配
+    // 只知道是 OOXML 时不区分表格、文档和演示文稿，直接按后缀匹配
+    pub fn classify(&self, extension: &str, content: Option<&ContentType>) -> Option<&FileCategory> {
+        content
+            .filter(|content| **content != sniff::OOXML)
+            .and_then(|content| self.categories.iter().find(|c| c.matches_content(content)))
+            .or_else(|| self.categories.iter().find(|c| c.extensions.contains(extension)))
+    }
+}
+
+// 规范化用户传入的后缀：去空白、转小写、补点号
+// 永远无法匹配的后缀 (空、含路径分隔符或空白、多段如 .tar.gz) 视为错误，整个列表不生效
+pub fn normalize_extensions(exts: &[String]) -> Result<HashSet<String>, MonitorError> {
+    let mut normalized = HashSet::new();
+    let mut invalid = vec![];
+    for ext in exts {
+        let trimmed = ext.trim().to_lowercase();
+        let name = trimmed.strip_prefix('.').unwrap_or(&trimmed);
+        if name.is_empty() || name.contains(['.', '/', '\\']) || name.contains(char::is_whitespace) {
+            invalid.push(format!("{:?}", ext));
+        } else {
+            normalized.insert(format!(".{}", name));
+        }
+    }
+    if invalid.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(MonitorError::new(format!(
+            "Invalid file extensions {}, only a single trailing extension such as \".xlsx\" is compared",
+            invalid.join(", ")
+        )))
+    }
+}
+
+// 规范化 MIME 类型：去空白、转小写；必须是 "type/subtype" 或 "type/*"
+pub fn normalize_mime_types(mime_types: &[String]) -> Result<HashSet<String>, MonitorError> {
+    let mut normalized = HashSet::new();
+    let mut invalid = vec![];
+    for mime in mime_types {
+        let trimmed = mime.trim().to_lowercase();
+        let valid = match trimmed.split_once('/') {
+            Some((major, minor)) => {
+                !major.is_empty() && major != "*" && !minor.is_empty() && !minor.contains(['/', ' '])
+            }
+            None => false,
+        };
+        if valid {
+            normalized.insert(trimmed);
+        } else {
+            invalid.push(format!("{:?}", mime));
+        }
+    }
+    if invalid.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(MonitorError::new(format!(
+            "Invalid MIME types {}, expected \"type/subtype\" or \"type/*\"",
+            invalid.join(", ")
+        )))
+    }
+}
+
+// 当前生效的分类规则
+pub fn file_rules() -> Arc<FileRules> {
+    FILE_RULES.read().unwrap().clone()
+}
+
+// 整体替换分类规则，正在进行的分析继续使用旧规则
+pub fn set_file_rules(rules: FileRules) {
+    *FILE_RULES.write().unwrap() = Arc::new(rules);
+}
+
+// 当前生效的路径规则
+pub fn path_rules() -> Arc<PathRules> {
+    PATH_RULES.read().unwrap().clone()
+}
+
+// 整体替换路径规则
+pub fn set_path_rules(rules: PathRules) {
+    *PATH_RULES.write().unwrap() = Arc::new(rules);
+}
@@ -0,0 +1,374 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+#[cfg(windows)]
+use windows::{
+    core::{ PCWSTR, Error },
+    Win32::Foundation::*,
+    Win32::System::DataExchange::*,
+    Win32::System::LibraryLoader::GetModuleHandleW,
+    Win32::UI::WindowsAndMessaging::*,
+};
+#[cfg(windows)]
+use crate::utils::to_wstring;
+#[cfg(any(windows, target_os = "linux", target_os = "freebsd"))]
+use crate::report_error_log;
+use crate::report_info_log;
+#[cfg(windows)]
+use crate::analysis::win32::{Win32Backend, CLIPBOARD_CHANGED};
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+use crate::analysis::{wayland::WaylandBackend, x11::X11Backend};
+#[cfg(target_os = "wasi")]
+use crate::analysis::host::HostBackend;
+#[cfg(not(windows))]
+use crate::analysis::host::close_host;
+use crate::utils::{check_throttle, current_thread_id, now_millis};
+use crate::global::{throttle_timer, Selection, LAST_IMG_SIZE, MONITOR_THREAD_ID};
+use crate::stats::{CHANGES_SEEN, CHANGES_THROTTLED, EVENTS_REPORTED, LAST_EVENT_AT, STARTED_AT};
+use crate::error::MonitorError;
+use crate::analysis::{analyze_clipboard, backend::ClipboardBackend, metadata::HashJob, ClipboardEvent};
+
+// 窗口过程函数
+#[cfg(windows)]
+unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_CLIPBOARDUPDATE => {
+            // 只做标记，真正的分析在 Win32Backend::wait_for_change 返回后进行
+            CLIPBOARD_CHANGED.with(|changed| changed.set(true));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let _ = unsafe { RemoveClipboardFormatListener(hwnd) };
+            unsafe { PostQuitMessage(0) };
+            LRESULT(0)
+        }
+        _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+    }
+}
+
+// 辅助函数：创建接收剪贴板消息的窗口，保持 Windows API 原始错误类型
+#[cfg(windows)]
+pub(crate) unsafe fn create_monitor_window() -> windows::core::Result<HWND> {
+    let instance = GetModuleHandleW(None)?;
+    let class_name = to_wstring("RustClipboardMonitor");
+
+    let wc = WNDCLASSW {
+        hCursor: LoadCursorW(None, IDC_ARROW)?,
+        hInstance: instance.into(),
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(wnd_proc),
+        ..Default::default()
+    };
+
+    // 重新启动监听时窗口类已经注册过
+    if RegisterClassW(&wc) == 0 {
+        let error = unsafe { GetLastError() };
+        if error != ERROR_CLASS_ALREADY_EXISTS {
+            report_error_log!("Window Registration Failed!");
+            return Err(Error::from(error));
+        }
+    }
+
+    let hwnd = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(to_wstring("Rust Monitor").as_ptr()),
+        WINDOW_STYLE::default(),
+        0, 0, 0, 0,
+        Some(HWND_MESSAGE),
+        None,
+        Some(instance.into()), //将 instance (HMODULE) 转换为 Option<HINSTANCE>
+        None,
+    );
+
+    hwnd.map_err(|_| {
+        report_error_log!("Window Creation Failed!");
+        Error::from(unsafe { GetLastError() })
+    })
+}
+
+// 监听循环：等待后端通知变化，节流后分析，由投递线程交给 on_event
+// 文件哈希由哈希线程在文件事件投递之后计算，完成后作为 Hashes 事件补发，监听线程和其他事件都不等待
+// 结束时中止未完成的哈希，等待已分析的事件投递完；后端正常停止时返回 Ok，因错误结束时返回该错误
+pub fn run_monitor<B: ClipboardBackend + ?Sized>(
+    backend: &mut B,
+    mut on_event: impl FnMut(ClipboardEvent) + Send,
+) -> Result<(), MonitorError> {
+    let (sender, receiver) = mpsc::channel::<ClipboardEvent>();
+    // 持有时分配序号并放入投递队列，保证投递顺序与序号一致；结束时取出发送端关闭队列
+    let queue = Mutex::new(Some(sender));
+    let (jobs, pending) = mpsc::channel::<HashJob>();
+    let cancel = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            for event in receiver {
+                on_event(event);
+            }
+        });
+        let hasher = scope.spawn(|| {
+            for job in pending {
+                let Some(hashes) = job.run(&cancel) else {
+                    continue;
+                };
+                if let Some(sender) = queue.lock().unwrap().as_ref() {
+                    let event = job.into_event(hashes);
+                    EVENTS_REPORTED.fetch_add(1, Ordering::Relaxed);
+                    LAST_EVENT_AT.store(event.timestamp, Ordering::Relaxed);
+                    let _ = sender.send(event);
+                }
+            }
+        });
+
+        while backend.wait_for_change() {
+            CHANGES_SEEN.fetch_add(1, Ordering::Relaxed);
+            if !check_throttle(throttle_timer(backend.selection())) {
+                CHANGES_THROTTLED.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            let guard = queue.lock().unwrap();
+            let Some(event) = analyze_clipboard(backend) else {
+                continue;
+            };
+            EVENTS_REPORTED.fetch_add(1, Ordering::Relaxed);
+            LAST_EVENT_AT.store(event.timestamp, Ordering::Relaxed);
+            let job = HashJob::new(&event);
+            if let Some(sender) = guard.as_ref() {
+                let _ = sender.send(event);
+            }
+            drop(guard);
+            if let Some(job) = job {
+                let _ = jobs.send(job);
+            }
+        }
+        cancel.store(true, Ordering::Relaxed);
+        drop(jobs);
+        let _ = hasher.join();
+        // 关闭投递队列，投递线程处理完剩余事件后退出
+        queue.lock().unwrap().take();
+        backend.take_error().map_or(Ok(()), Err)
+    })
+}
+
+// 打开当前平台的剪贴板后端
+// Windows 只有一个剪贴板，忽略 CLIPBOARD 以外的选区
+#[cfg(windows)]
+pub fn open_backend(selections: &[Selection]) -> Result<Box<dyn ClipboardBackend>, MonitorError> {
+    if selections.iter().any(|s| *s != Selection::CLIPBOARD) {
+        report_info_log!("Only the CLIPBOARD selection exists on Windows, others are ignored.");
+    }
+    Ok(Box::new(Win32Backend::new()?))
+}
+
+// Linux: Wayland 会话优先使用 data-control 协议，合成器不支持时退回 X11 (XWayland)
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn open_backend(selections: &[Selection]) -> Result<Box<dyn ClipboardBackend>, MonitorError> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match WaylandBackend::new(selections) {
+            Ok(backend) => {
+                report_info_log!("Using the Wayland data-control clipboard backend.");
+                return Ok(Box::new(backend));
+            }
+            Err(e) => report_error_log!("Wayland data-control unavailable ({}), falling back to X11.", e),
+        }
+    }
+
+    let backend = X11Backend::new(selections)?;
+    report_info_log!("Using the X11 clipboard backend, selections {:?}.", selections);
+    Ok(Box::new(backend))
+}
+
+// WASI: 没有系统剪贴板，内容由宿主 (浏览器 / Node) 通过 push_snapshot 推送
+#[cfg(target_os = "wasi")]
+pub fn open_backend(selections: &[Selection]) -> Result<Box<dyn ClipboardBackend>, MonitorError> {
+    report_info_log!("Using the host-driven clipboard backend, selections {:?}.", selections);
+    Ok(Box::new(HostBackend::new()))
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "freebsd", target_os = "wasi")))]
+pub fn open_backend(_selections: &[Selection]) -> Result<Box<dyn ClipboardBackend>, MonitorError> {
+    Err(MonitorError::new("Clipboard monitor is not supported on this platform"))
+}
+
+// 在当前线程上启动平台剪贴板监听，阻塞直到 stop_monitor 被调用或后端结束
+// 监听真正安装后调用 on_ready，因此调用方可以用它区分启动失败和运行中的失败
+// 所有平台共用同一套分析和上报
+pub fn setup_clipboard_monitor(
+    selections: &[Selection],
+    on_ready: impl FnOnce(),
+    on_event: impl FnMut(ClipboardEvent) + Send,
+) -> Result<(), MonitorError> {
+    MONITOR_THREAD_ID.store(current_thread_id(), Ordering::SeqCst);
+    let _reset = ResetOnExit;
+
+    open_backend(selections).and_then(|mut backend| {
+        report_info_log!("Monitoring started. Rust is watching your clipboard...");
+        STARTED_AT.store(now_millis(), Ordering::Relaxed);
+        on_ready();
+        run_monitor(backend.as_mut(), on_event)
+    })
+}
+
+// 监听结束后复位 (包括启动失败和 panic)，便于之后重新启动
+struct ResetOnExit;
+
+impl Drop for ResetOnExit {
+    fn drop(&mut self) {
+        MONITOR_THREAD_ID.store(0, Ordering::SeqCst);
+        STARTED_AT.store(0, Ordering::Relaxed);
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+    }
+}
+
+// 通知监听线程退出
+#[cfg(windows)]
+pub fn stop_monitor() {
+    let thread_id = MONITOR_THREAD_ID.load(Ordering::SeqCst);
+    if thread_id != 0 {
+        // 向后台线程发送 WM_QUIT，打破它的死循环
+        let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        report_info_log!("Sent WM_QUIT to monitor thread.");
+    }
+}
+
+// Linux: 关闭停止信号管道，唤醒阻塞在 poll 上的 X11 / Wayland 后端
+#[cfg(not(windows))]
+pub fn stop_monitor() {
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    crate::utils::signal_stop();
+    close_host();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::analysis::mock::{MockBackend, MockSnapshot};
+    use crate::analysis::pattern::PathRules;
+    use crate::analysis::ClipboardReport;
+    use crate::global::{
+        set_file_rules, set_path_rules, FileRules, DEFAULT_MAX_HASH_BYTES, HASH_BLAKE3, HASH_SHA256, MAX_HASH_BYTES,
+    };
+    use crate::utils::{lock_globals, test_dir};
+    use crate::EventKind;
+
+    // 每次变化前重置节流计时器，连续回放的快照都会被分析
+    // until 不为空时，快照回放完后等它置位 (最多 5 秒) 再结束监听，结束时未完成的哈希会被放弃
+    struct Unthrottled<'a> {
+        inner: MockBackend,
+        until: Option<&'a AtomicBool>,
+    }
+
+    impl Unthrottled<'_> {
+        fn new(snapshots: impl IntoIterator<Item = MockSnapshot>) -> Self {
+            Self { inner: snapshots.into_iter().collect(), until: None }
+        }
+    }
+
+    impl ClipboardBackend for Unthrottled<'_> {
+        fn formats(&mut self) -> Option<Vec<String>> {
+            self.inner.formats()
+        }
+
+        fn read(&mut self, format: &str) -> Option<Vec<u8>> {
+            self.inner.read(format)
+        }
+
+        fn owner(&mut self) -> Option<(u32, String)> {
+            self.inner.owner()
+        }
+
+        fn selection(&self) -> Selection {
+            self.inner.selection()
+        }
+
+        fn wait_for_change(&mut self) -> bool {
+            for selection in [Selection::CLIPBOARD, Selection::PRIMARY, Selection::SECONDARY] {
+                *throttle_timer(selection).lock().unwrap() = None;
+            }
+            if let (0, Some(until)) = (self.inner.pending(), self.until) {
+                let deadline = Instant::now() + Duration::from_secs(5);
+                while !until.load(Ordering::Relaxed) && Instant::now() < deadline {
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+            self.inner.wait_for_change()
+        }
+
+        fn take_error(&mut self) -> Option<MonitorError> {
+            self.inner.take_error()
+        }
+    }
+
+    #[test]
+    fn run_monitor_delivers_events_in_order() {
+        let _globals = lock_globals();
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        let mut backend = Unthrottled::new([
+            MockSnapshot::image(vec![1u8; 32]).with_owner(3, "screenshot"),
+            MockSnapshot::image(vec![2u8; 32]),
+            MockSnapshot::new().with_format("text/plain", "copied"),
+            MockSnapshot::new(),
+        ]);
+
+        let mut events = vec![];
+        run_monitor(&mut backend, |event| events.push(event)).unwrap();
+
+        let kinds: Vec<EventKind> = events.iter().map(|event| event.report.kind()).collect();
+        assert_eq!(kinds, vec![EventKind::IMAGE, EventKind::TEXT, EventKind::CLEARED]);
+        assert_eq!(events[0].pname, "screenshot");
+        assert!(events.windows(2).all(|pair| pair[1].seq > pair[0].seq));
+        let ClipboardReport::Text(text) = &events[1].report else { unreachable!() };
+        assert_eq!(text.preview, "copied");
+        assert_eq!(backend.inner.pending(), 0);
+    }
+
+    #[test]
+    fn file_hashes_follow_the_files_event() {
+        let _globals = lock_globals();
+        set_file_rules(FileRules::default());
+        set_path_rules(PathRules::default());
+        HASH_SHA256.store(true, Ordering::Relaxed);
+        HASH_BLAKE3.store(false, Ordering::Relaxed);
+        MAX_HASH_BYTES.store(1024, Ordering::Relaxed);
+        let dir = test_dir("hashes");
+        let path = dir.join("report.xlsx");
+        fs::write(&path, "plain text").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let hashed = AtomicBool::new(false);
+        let mut backend = Unthrottled::new([
+            MockSnapshot::files(std::slice::from_ref(&path)),
+            MockSnapshot::new().with_format("text/plain", "after"),
+        ]);
+        backend.until = Some(&hashed);
+
+        let mut events = vec![];
+        let result = run_monitor(&mut backend, |event| {
+            hashed.fetch_or(event.report.kind() == EventKind::HASHES, Ordering::Relaxed);
+            events.push(event);
+        });
+        HASH_SHA256.store(false, Ordering::Relaxed);
+        MAX_HASH_BYTES.store(DEFAULT_MAX_HASH_BYTES, Ordering::Relaxed);
+        let _ = fs::remove_dir_all(dir);
+        result.unwrap();
+
+        // 哈希线程可能在文本事件之前或之后完成，但文件事件一定先于它的哈希投递
+        let files_at = events.iter().position(|event| event.report.kind() == EventKind::FILES).expect("files event");
+        let hashes_at = events.iter().position(|event| event.report.kind() == EventKind::HASHES).expect("hashes event");
+        assert!(files_at < hashes_at);
+        assert_eq!(events.len(), 3);
+        assert!(events.windows(2).all(|pair| pair[1].seq > pair[0].seq));
+        let ClipboardReport::Hashes(hashes) = &events[hashes_at].report else { unreachable!() };
+        assert_eq!(hashes.event_seq, events[files_at].seq);
+        assert_eq!(hashes.files.len(), 1);
+        assert_eq!(hashes.files[0].path, path);
+        assert_eq!(hashes.files[0].category, "EXCEL");
+        assert_eq!(
+            hashes.files[0].sha256.as_deref(),
+            Some("c9ecf5e54c7b3f2640ecca21f96d4c3625a2b7935104f41c5ede29935a9e52c9")
+        );
+        assert_eq!(hashes.files[0].blake3, None);
+    }
+}
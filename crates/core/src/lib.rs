@@ -0,0 +1,20 @@
+#![deny(clippy::all)]
+pub mod global;
+pub mod error;
+mod utils;
+pub mod hooks;
+pub mod analysis;
+pub mod stats;
+
+pub use crate::analysis::{analyze_clipboard, ClipboardEvent, ClipboardReport};
+pub use crate::analysis::archive::{ArchiveFormat, ArchiveLimit};
+pub use crate::analysis::code::CodeLanguage;
+pub use crate::analysis::filter::{EventFilter, EventKind};
+pub use crate::analysis::pattern::{PathPattern, PathRules};
+pub use crate::analysis::walk::WalkLimit;
+pub use crate::error::MonitorError;
+pub use crate::global::{
+    ArchiveInfo, CategorySummary, CodeInfo, DirectoryInfo, FileCategory, FileHash, FileInfo, FileMetadata, FileRules, FilesInfo,
+    FilesSummary, HashesInfo, Selection, ShotInfo, TableInfo, TextInfo,
+};
+pub use crate::hooks::{open_backend, run_monitor, setup_clipboard_monitor, stop_monitor};
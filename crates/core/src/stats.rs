@@ -0,0 +1,196 @@
+// 监听运行状态和计数器，供宿主查询或导出为 Prometheus 指标
+// 计数器在多次启动之间累加，不会复位
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::global::MONITOR_THREAD_ID;
+
+// 监听安装成功的时间 (Unix 毫秒)，0 表示没有在运行
+pub static STARTED_AT: AtomicU64 = AtomicU64::new(0);
+// 最近一次上报事件的时间 (Unix 毫秒)，0 表示还没有事件
+pub static LAST_EVENT_AT: AtomicU64 = AtomicU64::new(0);
+// 后端通知的剪贴板变化次数
+pub static CHANGES_SEEN: AtomicU64 = AtomicU64::new(0);
+// 分析后上报的事件数
+pub static EVENTS_REPORTED: AtomicU64 = AtomicU64::new(0);
+// 被节流丢弃的变化次数
+pub static CHANGES_THROTTLED: AtomicU64 = AtomicU64::new(0);
+// 被图片大小去重过滤的次数
+pub static IMAGES_DEDUPLICATED: AtomicU64 = AtomicU64::new(0);
+// 宿主投递失败的事件数 (如 JS 回调队列已满)
+pub static EVENTS_DROPPED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Default)]
+pub struct MonitorStats {
+    pub running: bool,
+    // 监听线程 ID (Windows / Linux)，没有在运行时为 0
+    pub thread_id: u32,
+    pub started_at: u64,
+    pub last_event_at: u64,
+    pub changes_seen: u64,
+    pub events_reported: u64,
+    pub changes_throttled: u64,
+    pub images_deduplicated: u64,
+    pub events_dropped: u64,
+}
+
+pub fn monitor_stats() -> MonitorStats {
+    let started_at = STARTED_AT.load(Ordering::Relaxed);
+    MonitorStats {
+        running: started_at != 0,
+        thread_id: MONITOR_THREAD_ID.load(Ordering::Relaxed),
+        started_at,
+        last_event_at: LAST_EVENT_AT.load(Ordering::Relaxed),
+        changes_seen: CHANGES_SEEN.load(Ordering::Relaxed),
+        events_reported: EVENTS_REPORTED.load(Ordering::Relaxed),
+        changes_throttled: CHANGES_THROTTLED.load(Ordering::Relaxed),
+        images_deduplicated: IMAGES_DEDUPLICATED.load(Ordering::Relaxed),
+        events_dropped: EVENTS_DROPPED.load(Ordering::Relaxed),
+    }
+}
+
+// 宿主没能把事件交给调用方时记录
+pub fn record_dropped() {
+    EVENTS_DROPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+// 毫秒时间戳转为秒，0 保持为 0
+fn seconds(millis: u64) -> String {
+    if millis == 0 {
+        return "0".to_string();
+    }
+    format!("{}.{:03}", millis / 1000, millis % 1000)
+}
+
+impl MonitorStats {
+    // Prometheus 文本格式 (exposition format 0.0.4)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        write_metric(&mut out, "clipboard_monitor_up", "gauge",
+            "Whether the clipboard monitor is running.", if self.running { "1" } else { "0" });
+        write_metric(&mut out, "clipboard_monitor_start_time_seconds", "gauge",
+            "Unix time the clipboard monitor was started, 0 when stopped.", &seconds(self.started_at));
+        write_metric(&mut out, "clipboard_monitor_last_event_time_seconds", "gauge",
+            "Unix time of the last reported clipboard event, 0 when none.", &seconds(self.last_event_at));
+        write_metric(&mut out, "clipboard_monitor_changes_total", "counter",
+            "Clipboard changes notified by the platform backend.", &self.changes_seen.to_string());
+        write_metric(&mut out, "clipboard_monitor_events_total", "counter",
+            "Clipboard events reported after analysis.", &self.events_reported.to_string());
+        write_metric(&mut out, "clipboard_monitor_throttled_total", "counter",
+            "Clipboard changes skipped by the throttle.", &self.changes_throttled.to_string());
+        write_metric(&mut out, "clipboard_monitor_deduplicated_total", "counter",
+            "Image copies filtered as duplicates of the previous image.", &self.images_deduplicated.to_string());
+        write_metric(&mut out, "clipboard_monitor_dropped_total", "counter",
+            "Clipboard events that could not be delivered to a listener.", &self.events_dropped.to_string());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze_clipboard;
+    use crate::analysis::backend::ClipboardBackend;
+    use crate::analysis::mock::{MockBackend, MockSnapshot};
+    use crate::global::{throttle_timer, Selection, LAST_IMG_SIZE};
+    use crate::hooks::run_monitor;
+    use crate::utils::lock_globals;
+
+    fn sample() -> MonitorStats {
+        MonitorStats {
+            running: true,
+            thread_id: 42,
+            started_at: 1_700_000_000_005,
+            last_event_at: 1_700_000_123_450,
+            changes_seen: 12,
+            events_reported: 9,
+            changes_throttled: 2,
+            images_deduplicated: 1,
+            events_dropped: 3,
+        }
+    }
+
+    #[test]
+    fn prometheus_exposition_lists_every_counter() {
+        let expected = "\
+# HELP clipboard_monitor_up Whether the clipboard monitor is running.
+# TYPE clipboard_monitor_up gauge
+clipboard_monitor_up 1
+# HELP clipboard_monitor_start_time_seconds Unix time the clipboard monitor was started, 0 when stopped.
+# TYPE clipboard_monitor_start_time_seconds gauge
+clipboard_monitor_start_time_seconds 1700000000.005
+# HELP clipboard_monitor_last_event_time_seconds Unix time of the last reported clipboard event, 0 when none.
+# TYPE clipboard_monitor_last_event_time_seconds gauge
+clipboard_monitor_last_event_time_seconds 1700000123.450
+# HELP clipboard_monitor_changes_total Clipboard changes notified by the platform backend.
+# TYPE clipboard_monitor_changes_total counter
+clipboard_monitor_changes_total 12
+# HELP clipboard_monitor_events_total Clipboard events reported after analysis.
+# TYPE clipboard_monitor_events_total counter
+clipboard_monitor_events_total 9
+# HELP clipboard_monitor_throttled_total Clipboard changes skipped by the throttle.
+# TYPE clipboard_monitor_throttled_total counter
+clipboard_monitor_throttled_total 2
+# HELP clipboard_monitor_deduplicated_total Image copies filtered as duplicates of the previous image.
+# TYPE clipboard_monitor_deduplicated_total counter
+clipboard_monitor_deduplicated_total 1
+# HELP clipboard_monitor_dropped_total Clipboard events that could not be delivered to a listener.
+# TYPE clipboard_monitor_dropped_total counter
+clipboard_monitor_dropped_total 3
+";
+        assert_eq!(sample().to_prometheus(), expected);
+
+        // 没有在运行时时间戳为 0
+        let stopped = MonitorStats::default().to_prometheus();
+        assert!(stopped.contains("\nclipboard_monitor_up 0\n"));
+        assert!(stopped.contains("\nclipboard_monitor_start_time_seconds 0\n"));
+        assert!(stopped.contains("\nclipboard_monitor_last_event_time_seconds 0\n"));
+        // 每个指标一行 HELP、一行 TYPE、一行样本
+        assert_eq!(stopped.lines().count(), 8 * 3);
+    }
+
+    #[test]
+    fn monitor_updates_the_counters() {
+        let _globals = lock_globals();
+        *throttle_timer(Selection::CLIPBOARD).lock().unwrap() = None;
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+        let before = monitor_stats();
+
+        // 连续的变化中只有第一个通过节流
+        let mut backend: MockBackend = [
+            MockSnapshot::new().with_format("text/plain", "first"),
+            MockSnapshot::new().with_format("text/plain", "second"),
+            MockSnapshot::new().with_format("text/plain", "third"),
+        ]
+        .into_iter()
+        .collect();
+        let mut events = vec![];
+        run_monitor(&mut backend, |event| events.push(event)).unwrap();
+        let after = monitor_stats();
+        assert_eq!(events.len(), 1);
+        assert_eq!(after.changes_seen - before.changes_seen, 3);
+        assert_eq!(after.changes_throttled - before.changes_throttled, 2);
+        assert_eq!(after.events_reported - before.events_reported, 1);
+        assert_eq!(after.last_event_at, events[0].timestamp);
+
+        // 与上一张图片大小相同的图片被过滤
+        let mut images: MockBackend = [MockSnapshot::image(vec![1u8; 32]), MockSnapshot::image(vec![2u8; 32])].into_iter().collect();
+        for _ in 0..2 {
+            assert!(images.wait_for_change());
+            analyze_clipboard(&mut images);
+        }
+        assert_eq!(monitor_stats().images_deduplicated - after.images_deduplicated, 1);
+
+        record_dropped();
+        record_dropped();
+        assert_eq!(monitor_stats().events_dropped - after.events_dropped, 2);
+        LAST_IMG_SIZE.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::{Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+#[cfg(windows)]
+use windows::{
+    core::{ Result, Error },
+    Win32::Foundation::{GetLastError, HWND, MAX_PATH, CloseHandle},
+    Win32::System::ProcessStatus::GetModuleBaseNameW,
+    Win32::UI::WindowsAndMessaging::*,
+    Win32::System::Threading::{GetCurrentThreadId, OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ},
+};
+use crate::global::THROTTLE_MS;
+
+// 辅助：将 Rust 字符串转换为 Windows 宽字符串 (UTF-16)
+#[cfg(windows)]
+pub fn to_wstring(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+// 辅助函数：检查是否允许打印（节流逻辑）
+pub fn check_throttle(timer: &Mutex<Option<Instant>>) -> bool {
+    // 获取锁
+    let mut guard = timer.lock().unwrap();
+    let now = Instant::now();
+
+    if let Some(last_time) = *guard {
+        // 如果距离上次打印的时间小于阈值，则阻止打印
+        if now.duration_since(last_time) < Duration::from_millis(THROTTLE_MS) {
+            return false;
+        }
+    }
+
+    // 更新最后打印时间
+    *guard = Some(now);
+    true
+}
+
+// 测试共用：读写全局状态 (规则、上限、图片去重记录、节流计时器) 的测试持有这把锁串行执行
+#[cfg(test)]
+pub fn lock_globals() -> std::sync::MutexGuard<'static, ()> {
+    static GLOBALS: Mutex<()> = Mutex::new(());
+    GLOBALS.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+// 测试共用：在系统临时目录下创建一个空目录，名称带进程号，避免同时运行的测试进程互相影响
+#[cfg(test)]
+pub fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("clipboard-copy-aware-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// 当前 Unix 时间戳 (毫秒)
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// 当前线程的系统线程 ID，Windows 以外只有 Linux 能取到，其余平台为 0
+pub fn current_thread_id() -> u32 {
+    #[cfg(windows)]
+    return unsafe { GetCurrentThreadId() };
+    #[cfg(target_os = "linux")]
+    return rustix::thread::gettid().as_raw_nonzero().get() as u32;
+    #[cfg(not(any(windows, target_os = "linux")))]
+    0
+}
+
+#[cfg(windows)]
+fn last_error() -> Error {
+    let code = unsafe { GetLastError() };
+    Error::from(code)
+}
+
+#[cfg(windows)]
+pub unsafe fn get_process_info(hwnd: HWND) -> Result<(u32, String)> {
+    let mut pid: u32 = 0;
+    // 获取关联的 PID
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+
+    if pid == 0 {
+        return Err(last_error());
+    }
+
+    // 打开进程以查询信息
+    // 注意：PROCESS_QUERY_INFORMATION 和 PROCESS_VM_READ 是必须的权限
+    let process_handle = unsafe {
+        OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)?
+    };
+
+    if process_handle.is_invalid() {
+        return Err(last_error());
+    }
+
+    // 获取进程名
+    let mut buffer = [0u16; MAX_PATH as usize];
+    // GetModuleBaseNameW 获取的是文件名 (例如 "notepad.exe")
+    // 如果需要完整路径，可以使用 GetModuleFileNameExW
+    let len = unsafe {
+        GetModuleBaseNameW(process_handle, None, &mut buffer)    
+    };
+    
+    // 关闭句柄防止泄露
+    let _ = unsafe { CloseHandle(process_handle) };
+
+    if len == 0 {
+        return Err(last_error());
+    }
+
+    // 将 u16 数组转换为 String
+    let name = String::from_utf16_lossy(&buffer[..len as usize]);
+    
+    Ok((pid, name))
+}
+// 通过 /proc 获取进程名，没有挂载 procfs 时返回 None
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn get_process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim_end().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+// 停止信号：stop_monitor 关闭管道写端，阻塞在 poll 上的监听线程读到 EOF 后退出
+// 只保留最近一次创建的信号，之前的写端随之关闭
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+static STOP_WRITER: Mutex<Option<std::io::PipeWriter>> = Mutex::new(None);
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn stop_signal() -> std::io::Result<std::io::PipeReader> {
+    let (reader, writer) = std::io::pipe()?;
+    *STOP_WRITER.lock().unwrap() = Some(writer);
+    Ok(reader)
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn signal_stop() {
+    STOP_WRITER.lock().unwrap().take();
+}
+
+// 等待连接可读，收到停止信号或 poll 失败时返回 false
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn wait_readable(conn: std::os::fd::BorrowedFd, stop: &std::io::PipeReader) -> bool {
+    use rustix::event::{poll, PollFd, PollFlags};
+
+    let mut fds = [
+        PollFd::from_borrowed_fd(conn, PollFlags::IN),
+        PollFd::new(stop, PollFlags::IN),
+    ];
+    loop {
+        match poll(&mut fds, None) {
+            Ok(_) => return fds[1].revents().is_empty(),
+            Err(rustix::io::Errno::INTR) => continue,
+            Err(_) => return false,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// 在无头的 wlroots 合成器 (sway) 上验证 Wayland data-control 后端，需要 WAYLAND_DISPLAY 和 wl-copy，默认忽略
+// CI 中先启动 WLR_BACKENDS=headless sway，再运行：cargo test -p clipboard-copy-aware-core --test wayland -- --ignored
+#![cfg(any(target_os = "linux", target_os = "freebsd"))]
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clipboard_copy_aware_core::{setup_clipboard_monitor, stop_monitor, ClipboardEvent, ClipboardReport, EventKind, Selection};
+
+// 等待单个事件的时间，足够覆盖节流间隔和 wl-copy 启动
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+// 两次复制之间的间隔，大于监听的节流间隔
+const COPY_INTERVAL: Duration = Duration::from_millis(700);
+
+// 由 wl-copy 持有剪贴板并提供指定 MIME 类型的内容
+fn copy(mime: &str, data: &[u8]) {
+    let mut wl_copy = Command::new("wl-copy")
+        .args(["--type", mime])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("wl-copy is required to run this test");
+    wl_copy.stdin.take().unwrap().write_all(data).unwrap();
+    // wl-copy 读完输入后转入后台持有剪贴板，前台进程随即退出
+    assert!(wl_copy.wait().unwrap().success());
+}
+
+// 跳过哈希等后续事件，等待下一个指定类型的事件
+fn next_event(events: &Receiver<ClipboardEvent>, kind: EventKind) -> ClipboardEvent {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let event = events.recv_timeout(remaining).unwrap_or_else(|_| panic!("no {:?} event", kind));
+        if event.report.kind() == kind {
+            return event;
+        }
+    }
+}
+
+#[test]
+#[ignore = "requires a Wayland compositor with data-control and wl-copy"]
+fn wayland_copies_are_reported() {
+    assert!(std::env::var_os("WAYLAND_DISPLAY").is_some(), "WAYLAND_DISPLAY is not set");
+    let (ready_tx, ready) = mpsc::channel();
+    let (event_tx, events) = mpsc::channel();
+    let monitor = thread::spawn(move || {
+        setup_clipboard_monitor(
+            &[Selection::CLIPBOARD],
+            move || ready_tx.send(()).unwrap(),
+            move |event| {
+                let _ = event_tx.send(event);
+            },
+        )
+    });
+    if ready.recv_timeout(EVENT_TIMEOUT).is_err() {
+        panic!("monitor failed to start: {:?}", monitor.join().unwrap());
+    }
+
+    copy("text/plain;charset=utf-8", "copied from wl-copy".as_bytes());
+    let event = next_event(&events, EventKind::TEXT);
+    let ClipboardReport::Text(text) = &event.report else { unreachable!() };
+    assert_eq!(text.preview, "copied from wl-copy");
+    assert_eq!(event.selection, Selection::CLIPBOARD);
+    // data-control 协议不提供数据源的进程
+    assert_eq!(event.pid, 0);
+
+    thread::sleep(COPY_INTERVAL);
+    let dir = std::env::temp_dir().join(format!("clipboard-copy-aware-wayland-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.xlsx");
+    fs::write(&path, "plain text").unwrap();
+    copy("text/uri-list", format!("file://{}\r\n", path.display()).as_bytes());
+    let event = next_event(&events, EventKind::FILES);
+    let ClipboardReport::Files(files) = &event.report else { unreachable!() };
+    let found: Vec<(&str, &str)> = files.files.iter().map(|f| (f.path.as_str(), f.category.as_str())).collect();
+    assert_eq!(found, vec![(path.to_str().unwrap(), "EXCEL")]);
+
+    thread::sleep(COPY_INTERVAL);
+    // 清空剪贴板
+    assert!(Command::new("wl-copy").arg("--clear").status().unwrap().success());
+    next_event(&events, EventKind::CLEARED);
+
+    stop_monitor();
+    let _ = fs::remove_dir_all(dir);
+    monitor.join().unwrap().expect("monitor stops cleanly");
+}
@@ -0,0 +1,83 @@
+// 在真实的 X 服务器上验证 X11 后端，需要 DISPLAY 和 xclip，默认忽略
+// CI 中在 Xvfb 下运行：xvfb-run -a cargo test -p clipboard-copy-aware-core --test x11 -- --ignored
+#![cfg(any(target_os = "linux", target_os = "freebsd"))]
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clipboard_copy_aware_core::{setup_clipboard_monitor, stop_monitor, ClipboardEvent, ClipboardReport, EventKind, Selection};
+
+// 等待单个事件的时间，足够覆盖节流间隔和 xclip 启动
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+// 两次复制之间的间隔，大于监听的节流间隔
+const COPY_INTERVAL: Duration = Duration::from_millis(700);
+
+// 由 xclip 持有 CLIPBOARD 选区并提供指定 target 的内容
+fn copy(target: &str, data: &[u8]) {
+    let mut xclip = Command::new("xclip")
+        .args(["-selection", "clipboard", "-t", target])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("xclip is required to run this test");
+    xclip.stdin.take().unwrap().write_all(data).unwrap();
+    // xclip 读完输入后转入后台持有选区，前台进程随即退出
+    assert!(xclip.wait().unwrap().success());
+}
+
+// 跳过哈希等后续事件，等待下一个指定类型的事件
+fn next_event(events: &Receiver<ClipboardEvent>, kind: EventKind) -> ClipboardEvent {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let event = events.recv_timeout(remaining).unwrap_or_else(|_| panic!("no {:?} event", kind));
+        if event.report.kind() == kind {
+            return event;
+        }
+    }
+}
+
+#[test]
+#[ignore = "requires an X server and xclip"]
+fn x11_copies_are_reported() {
+    let (ready_tx, ready) = mpsc::channel();
+    let (event_tx, events) = mpsc::channel();
+    let monitor = thread::spawn(move || {
+        setup_clipboard_monitor(
+            &[Selection::CLIPBOARD],
+            move || ready_tx.send(()).unwrap(),
+            move |event| {
+                let _ = event_tx.send(event);
+            },
+        )
+    });
+    if ready.recv_timeout(EVENT_TIMEOUT).is_err() {
+        panic!("monitor failed to start: {:?}", monitor.join().unwrap());
+    }
+
+    copy("UTF8_STRING", "copied from xclip".as_bytes());
+    let event = next_event(&events, EventKind::TEXT);
+    let ClipboardReport::Text(text) = &event.report else { unreachable!() };
+    assert_eq!(text.preview, "copied from xclip");
+    assert_eq!(event.selection, Selection::CLIPBOARD);
+    // X-Resource 可以查到本地客户端的 pid
+    assert!(event.pname.contains("xclip"), "unexpected owner {:?}", event.pname);
+
+    thread::sleep(COPY_INTERVAL);
+    let dir = std::env::temp_dir().join(format!("clipboard-copy-aware-x11-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.xlsx");
+    fs::write(&path, "plain text").unwrap();
+    copy("text/uri-list", format!("file://{}\r\n", path.display()).as_bytes());
+    let event = next_event(&events, EventKind::FILES);
+    let ClipboardReport::Files(files) = &event.report else { unreachable!() };
+    let found: Vec<(&str, &str)> = files.files.iter().map(|f| (f.path.as_str(), f.category.as_str())).collect();
+    assert_eq!(found, vec![(path.to_str().unwrap(), "EXCEL")]);
+
+    stop_monitor();
+    let _ = fs::remove_dir_all(dir);
+    monitor.join().unwrap().expect("monitor stops cleanly");
+}
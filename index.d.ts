@@ -1,24 +1,295 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
-export declare function clipboardInitialize(codeExts: Array<string>, imagExts: Array<string>, exceExts: Array<string>, reportFile: ((err: Error | null, arg: Array<FileInfo>) => any), reportShot: ((err: Error | null, arg: ShotInfo) => any), log: ((err: Error | null, arg: string) => any)): void
+export declare class ClipboardWatcher {
+  next(): Promise<IteratorResult<ClipboardEvent, undefined>>
+  return(): Promise<IteratorResult<ClipboardEvent, undefined>>
+  close(): void
+  get dropped(): number
+}
+
+export declare class Subscription {
+  unsubscribe(): void
+  get active(): boolean
+}
+
+export declare const enum ArchiveFormat {
+  ZIP = 0,
+  TAR = 1,
+  TARGZ = 2,
+  SEVENZIP = 3
+}
+
+export interface ArchiveInfo {
+  path: string
+  format: ArchiveFormat
+  entries: number
+  categories: Record<string, number>
+  limit?: ArchiveLimit
+  error?: string
+}
+
+export declare const enum ArchiveLimit {
+  DEPTH = 0,
+  ENTRIES = 1,
+  BYTES = 2,
+  TIME = 3
+}
+
+export interface CategorySummary {
+  files: number
+  bytes: number
+}
+
+export interface ClipboardEvent {
+  seq: number
+  timestamp: number
+  pid: number
+  pname: string
+  selection: Selection
+  formats: Array<string>
+  payload: ClipboardPayload
+}
+
+export declare const enum ClipboardEventKind {
+  FILES = 0,
+  IMAGE = 1,
+  TABLE = 2,
+  TEXT = 3,
+  CLEARED = 4,
+  CODE = 5,
+  HASHES = 6
+}
+
+export interface ClipboardFormat {
+  format: string
+  data: Uint8Array
+}
+
+export declare function clipboardInitialize(categories: Array<FileCategory>, reportFile: ((err: Error | null, arg: Array<FileInfo>) => unknown), reportShot: ((err: Error | null, arg: ShotInfo) => unknown), log: ((err: Error | null, arg: string) => any), options?: MonitorOptions | undefined | null): Promise<void>
+
+export declare function clipboardOnError(callback: ((err: Error | null, arg: MonitorErrorInfo) => any)): void
+
+export declare function clipboardOnEvent(callback: ((err: Error | null, arg: ClipboardEvent) => unknown)): void
+
+export type ClipboardPayload =
+  | { kind: 'Files'; files: Array<FileInfo>; directories: Array<DirectoryInfo>; archives: Array<ArchiveInfo>; summary: FilesSummary; truncated: boolean }
+  | { kind: 'Image'; image: ShotInfo }
+  | { kind: 'Table'; table: TableInfo }
+  | { kind: 'Text'; text: TextInfo }
+  | { kind: 'Code'; code: CodeInfo }
+  | { kind: 'Cleared' }
+  | { kind: 'Hashes'; hashes: HashesInfo }
+
+export declare function clipboardPushSnapshot(snapshot: ClipboardSnapshot): void
+
+export declare function clipboardShutdown(): void
+
+export interface ClipboardSnapshot {
+  formats: Array<ClipboardFormat>
+  pid?: number
+  pname?: string
+  selection?: Selection
+}
+
+export interface ClipboardWatchResult {
+  value?: ClipboardEvent
+  done: boolean
+}
+
+export interface CodeInfo {
+  text: TextInfo
+  language: CodeLanguage
+  confidence: number
+}
+
+export declare const enum CodeLanguage {
+  RUST = 0,
+  C = 1,
+  CPP = 2,
+  CSHARP = 3,
+  JAVA = 4,
+  GO = 5,
+  PYTHON = 6,
+  JAVASCRIPT = 7,
+  TYPESCRIPT = 8,
+  SQL = 9,
+  SHELL = 10
+}
+
+export interface DirectoryInfo {
+  path: string
+  files: number
+  categories: Record<string, number>
+  limit?: WalkLimit
+}
+
+export interface FileCategory {
+  name: string
+  extensions?: Array<string>
+  mimeTypes?: Array<string>
+  priority?: number
+}
+
+export interface FileHash {
+  path: string
+  category: string
+  sha256?: string
+  blake3?: string
+}
 
 export interface FileInfo {
   name: string
   path: string
-  fileType: FileType
+  category: string
   extension: string
+  mimeType?: string
+  extensionMismatch: boolean
+  directory?: string
+  archiveEntry?: string
+  size?: number
+  modifiedAt?: number
+  createdAt?: number
+  uid?: number
+  permissions?: number
+  readonly: boolean
   pname: string
   pid: number
+  selection: Selection
+}
+
+export interface FilesSummary {
+  files: number
+  bytes: number
+  categories: Record<string, CategorySummary>
+}
+
+export declare function getPrometheusMetrics(): string
+
+export declare function getStatus(): MonitorStatus
+
+export declare const enum HashAlgorithm {
+  SHA256 = 0,
+  BLAKE3 = 1
+}
+
+export interface HashesInfo {
+  eventSeq: number
+  files: Array<FileHash>
+}
+
+export interface MonitorConfig {
+  categories?: Array<FileCategory>
+  pathRules?: Array<PathRule>
+  maxTableCells?: number
+  textPreviewChars?: number
+  maxWalkDepth?: number
+  maxWalkFiles?: number
+  walkTimeoutMs?: number
+  maxArchiveDepth?: number
+  maxArchiveEntries?: number
+  maxArchiveBytes?: number
+  hashAlgorithms?: Array<HashAlgorithm>
+  maxHashBytes?: number
+  reportUnmatched?: boolean
+  detectCode?: boolean
+}
+
+export interface MonitorErrorInfo {
+  message: string
+  code: number
+}
+
+export interface MonitorOptions {
+  selections?: Array<Selection>
+  maxTableCells?: number
+  textPreviewChars?: number
+  pathRules?: Array<PathRule>
+  maxWalkDepth?: number
+  maxWalkFiles?: number
+  walkTimeoutMs?: number
+  maxArchiveDepth?: number
+  maxArchiveEntries?: number
+  maxArchiveBytes?: number
+  hashAlgorithms?: Array<HashAlgorithm>
+  maxHashBytes?: number
+  reportUnmatched?: boolean
+  detectCode?: boolean
+}
+
+export interface MonitorStatus {
+  running: boolean
+  threadId?: number
+  startedAt?: number
+  lastEventAt?: number
+  changesSeen: number
+  eventsReported: number
+  changesThrottled: number
+  imagesDeduplicated: number
+  eventsDropped: number
 }
 
-export declare const enum FileType {
-  IMAGE = 0,
-  CODE = 1,
-  EXCEL = 2
+export interface PathRule {
+  glob?: string
+  regex?: string
+  exclude?: boolean
+  caseSensitive?: boolean
+}
+
+export declare const enum Selection {
+  CLIPBOARD = 0,
+  PRIMARY = 1,
+  SECONDARY = 2
 }
 
 export interface ShotInfo {
   pname: string
   pid: number
   data: Uint8Array
+  selection: Selection
+}
+
+export declare function subscribe(filter: SubscriptionFilter | undefined | null, callback: ((err: Error | null, arg: ClipboardEvent) => unknown)): Subscription
+
+export interface SubscriptionFilter {
+  kinds?: Array<ClipboardEventKind>
+  categories?: Array<string>
+  pnames?: Array<string>
+}
+
+export interface TableInfo {
+  pname: string
+  pid: number
+  format: string
+  selection: Selection
+  rows: number
+  columns: number
+  cells: Array<Array<string>>
+  truncated: boolean
+}
+
+export interface TextInfo {
+  pname: string
+  pid: number
+  format: string
+  selection: Selection
+  chars: number
+  bytes: number
+  lines: number
+  preview: string
+  hash: string
+}
+
+export declare function updateConfig(config: MonitorConfig): void
+
+export declare const enum WalkLimit {
+  DEPTH = 0,
+  FILES = 1,
+  TIME = 2
+}
+
+export declare function watchClipboard(options?: WatchOptions | undefined | null): ClipboardWatcher & AsyncIterableIterator<ClipboardEvent>
+
+export interface WatchOptions {
+  capacity?: number
+  filter?: SubscriptionFilter
 }
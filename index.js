@@ -577,4 +577,21 @@ if (!nativeBinding) {
 
 module.exports = nativeBinding
 module.exports.clipboardInitialize = nativeBinding.clipboardInitialize
-module.exports.FileType = nativeBinding.FileType
+module.exports.Selection = nativeBinding.Selection
+module.exports.clipboardPushSnapshot = nativeBinding.clipboardPushSnapshot
+module.exports.clipboardOnEvent = nativeBinding.clipboardOnEvent
+module.exports.clipboardShutdown = nativeBinding.clipboardShutdown
+module.exports.updateConfig = nativeBinding.updateConfig
+module.exports.ClipboardWatcher = nativeBinding.ClipboardWatcher
+module.exports.watchClipboard = nativeBinding.watchClipboard
+module.exports.ClipboardEventKind = nativeBinding.ClipboardEventKind
+module.exports.Subscription = nativeBinding.Subscription
+module.exports.subscribe = nativeBinding.subscribe
+module.exports.clipboardOnError = nativeBinding.clipboardOnError
+module.exports.getStatus = nativeBinding.getStatus
+module.exports.getPrometheusMetrics = nativeBinding.getPrometheusMetrics
+module.exports.WalkLimit = nativeBinding.WalkLimit
+module.exports.ArchiveFormat = nativeBinding.ArchiveFormat
+module.exports.ArchiveLimit = nativeBinding.ArchiveLimit
+module.exports.HashAlgorithm = nativeBinding.HashAlgorithm
+module.exports.CodeLanguage = nativeBinding.CodeLanguage
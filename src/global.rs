@@ -1,50 +1,167 @@
-use std::sync::atomic::{AtomicU32, AtomicUsize};
-use std::sync::{Mutex, OnceLock};
-use std::time::Instant;
-use std::collections::HashSet;
-use std::fmt;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
 use napi_derive::napi;
-use napi::bindgen_prelude::Uint8Array;
+use napi::bindgen_prelude::{JsObjectValue, JsValue, ToNapiValue, Uint8Array, Unknown};
 use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Status};
 
-// 使用 Mutex 记录上一次打印的时间
-// 注意：这里使用 Mutex 是因为我们需要在 immutable 的静态上下文中修改时间
-pub static LAST_UPDATE_LOG: Mutex<Option<Instant>> = Mutex::new(None);
-// 要监控的文件后缀
-pub static CODE_EXTENSIONS: OnceLock<HashSet<String>> = OnceLock::new();
-pub static IMAG_EXTENSIONS: OnceLock<HashSet<String>> = OnceLock::new();
-pub static EXCE_EXTENSIONS: OnceLock<HashSet<String>> = OnceLock::new();
+use clipboard_copy_aware_core as aware;
 
-pub static GLOBAL_REPORT: OnceLock<ThreadsafeFunction<Vec<FileInfo>>> = OnceLock::new();
-pub static GLOBAL_REPORT_SHOT: OnceLock<ThreadsafeFunction<ShotInfo>> = OnceLock::new();
-pub static GLOBAL_LOG: OnceLock<ThreadsafeFunction<String>> = OnceLock::new();
+// 上报事件的 JS 回调最多积压的调用数，超出时丢弃并计入 eventsDropped
+pub const EVENT_QUEUE_SIZE: usize = 1000;
 
-// 用于记录后台监控线程的 ID
-pub static MONITOR_THREAD_ID: AtomicU32 = AtomicU32::new(0);
-// 用于记录上一次图片的字节大小，用于过滤重复事件
-pub static LAST_IMG_SIZE: AtomicUsize = AtomicUsize::new(0);
+// 上报事件的 JS 回调，队列有上限
+pub type EventCallback<T> = ThreadsafeFunction<T, Unknown<'static>, T, Status, true, false, EVENT_QUEUE_SIZE>;
 
-// === 节流控制配置 ===
-// 定义节流时间阈值：500毫秒
-pub const THROTTLE_MS: u64 = 500;
+// JS 回调，clipboard_shutdown 时释放，之后可以重新注册
+pub static GLOBAL_REPORT: Mutex<Option<EventCallback<Vec<FileInfo>>>> = Mutex::new(None);
+pub static GLOBAL_REPORT_SHOT: Mutex<Option<EventCallback<ShotInfo>>> = Mutex::new(None);
+pub static GLOBAL_LOG: Mutex<Option<ThreadsafeFunction<String>>> = Mutex::new(None);
+pub static GLOBAL_EVENT: Mutex<Option<EventCallback<ClipboardEvent>>> = Mutex::new(None);
+pub static GLOBAL_ERROR: Mutex<Option<ThreadsafeFunction<MonitorErrorInfo>>> = Mutex::new(None);
 
+// 以下类型是 core 中同名类型在 JS 侧的映射
+
+// 事件来源的选区：Windows 只有 CLIPBOARD，X11 还有 PRIMARY (划词) 和 SECONDARY
 #[napi]
 #[derive(Debug)]
-pub enum FileType {
-    IMAGE,
-    CODE,
-    EXCEL
+pub enum Selection {
+    CLIPBOARD,
+    PRIMARY,
+    SECONDARY
 }
 
 #[napi(object)]
 pub struct FileInfo {
     pub name: String,
     pub path: String,
-    pub file_type: FileType,
+    // 匹配到的分类名称
+    pub category: String,
+    // 声明的后缀，没有后缀时为空串
     pub extension: String,
+    // 按文件头识别出的 MIME 类型
+    pub mime_type: Option<String>,
+    // 文件内容与后缀不一致
+    pub extension_mismatch: bool,
+    // 在复制的文件夹中找到时为该文件夹的路径
+    pub directory: Option<String>,
+    // 在压缩包中找到时为条目在压缩包中的路径 (嵌套的压缩包之间用 "!/" 分隔)，此时 path 是压缩包的路径
+    pub archive_entry: Option<String>,
+    // 字节数，压缩包中的条目为解压后的大小
+    pub size: Option<i64>,
+    // 修改时间和创建时间 (Unix 毫秒)
+    pub modified_at: Option<i64>,
+    pub created_at: Option<i64>,
+    // 所有者 uid 和权限位，只有 Unix 有
+    pub uid: Option<u32>,
+    pub permissions: Option<u32>,
+    pub readonly: bool,
     pub pname: String,
     pub pid: u32,
+    pub selection: Selection,
+}
+
+// 可以为复制的文件计算的哈希
+#[napi]
+#[derive(Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    SHA256,
+    BLAKE3
+}
+
+// 代码片段识别出的编程语言
+#[napi]
+#[derive(Debug)]
+pub enum CodeLanguage {
+    RUST,
+    C,
+    CPP,
+    CSHARP,
+    JAVA,
+    GO,
+    PYTHON,
+    JAVASCRIPT,
+    TYPESCRIPT,
+    SQL,
+    SHELL
+}
+
+// 文件夹遍历提前结束的原因
+#[napi]
+#[derive(Debug)]
+pub enum WalkLimit {
+    // 有子目录超过 maxWalkDepth，没有进入
+    DEPTH,
+    // 遍历的文件数达到 maxWalkFiles
+    FILES,
+    // 遍历时间达到 walkTimeoutMs
+    TIME
+}
+
+// 复制的文件夹的遍历结果
+#[napi(object)]
+pub struct DirectoryInfo {
+    pub path: String,
+    // 遍历到的文件数
+    pub files: u32,
+    // 每个分类匹配到的文件数，如 { EXCEL: 1200, CODE: 3 }
+    pub categories: HashMap<String, u32>,
+    // 没有遍历完时为达到的限制
+    pub limit: Option<WalkLimit>,
+}
+
+// 一个分类的文件数和字节数
+#[napi(object)]
+pub struct CategorySummary {
+    pub files: u32,
+    pub bytes: i64,
+}
+
+// 复制的文件按分类的汇总，不包括压缩包中的条目
+#[napi(object)]
+pub struct FilesSummary {
+    pub files: u32,
+    pub bytes: i64,
+    pub categories: HashMap<String, CategorySummary>,
+}
+
+#[napi]
+#[derive(Debug)]
+pub enum ArchiveFormat {
+    ZIP,
+    TAR,
+    TARGZ,
+    SEVENZIP
+}
+
+// 压缩包没有列完的原因
+#[napi]
+#[derive(Debug)]
+pub enum ArchiveLimit {
+    // 有嵌套的压缩包超过 maxArchiveDepth，没有打开
+    DEPTH,
+    // 列出的条目数达到 maxArchiveEntries，或压缩包目录中的条目数超过剩余额度 (此时不列出该压缩包)
+    ENTRIES,
+    // 解压的字节数 (包括 zip / 7z 的目录) 达到 maxArchiveBytes
+    BYTES,
+    // 在复制的文件夹中找到的压缩包，列出时达到 walkTimeoutMs
+    TIME
+}
+
+// 复制的压缩包的列出结果
+#[napi(object)]
+pub struct ArchiveInfo {
+    pub path: String,
+    pub format: ArchiveFormat,
+    // 列出的文件数，包括嵌套压缩包中的
+    pub entries: u32,
+    // 每个分类匹配到的条目数
+    pub categories: HashMap<String, u32>,
+    // 没有列完时为达到的限制
+    pub limit: Option<ArchiveLimit>,
+    // 压缩包损坏或格式不支持时的错误
+    pub error: Option<String>,
 }
 
 #[napi(object)]
@@ -53,93 +170,696 @@ pub struct ShotInfo {
     pub pid: u32,
     // 图片数据
     pub data: Uint8Array,
+    pub selection: Selection,
 }
 
-pub fn report_file(files: Vec<FileInfo>) {
-    if let Some(tsfn) = GLOBAL_REPORT.get() {
-        tsfn.call(
-            Ok(files), ThreadsafeFunctionCallMode::NonBlocking);
-    } else {
-        println!("Warning: No report wnd listener registered yet!");
+#[napi(object)]
+pub struct TableInfo {
+    pub pname: String,
+    pub pid: u32,
+    // 数据来源的剪贴板格式
+    pub format: String,
+    pub selection: Selection,
+    pub rows: u32,
+    // 最宽一行的列数
+    pub columns: u32,
+    // 按行排列的单元格，超过 maxTableCells 的部分被截掉
+    pub cells: Vec<Vec<String>>,
+    pub truncated: bool,
+}
+
+#[napi(object)]
+pub struct TextInfo {
+    pub pname: String,
+    pub pid: u32,
+    // 数据来源的剪贴板格式
+    pub format: String,
+    pub selection: Selection,
+    pub chars: u32,
+    // UTF-8 编码后的字节数
+    pub bytes: u32,
+    pub lines: u32,
+    // 开头最多 textPreviewChars 个字符
+    pub preview: String,
+    // 文本 UTF-8 内容的 SHA-256 (十六进制)
+    pub hash: String,
+}
+
+// 识别为源代码的文本，language 和 confidence 按关键字和符号启发式估计
+#[napi(object)]
+pub struct CodeInfo {
+    pub text: TextInfo,
+    pub language: CodeLanguage,
+    // 0 ~ 1
+    pub confidence: f64,
+}
+
+// 一个文件按 hashAlgorithms 计算的内容哈希 (十六进制)
+#[napi(object)]
+pub struct FileHash {
+    pub path: String,
+    pub category: String,
+    pub sha256: Option<String>,
+    pub blake3: Option<String>,
+}
+
+// 文件事件的哈希，在文件事件投递之后由后台线程计算，eventSeq 为对应文件事件的 seq
+// 压缩包中的条目、超过 maxHashBytes 和读取失败的文件不在其中
+#[napi(object)]
+pub struct HashesInfo {
+    pub event_seq: i64,
+    pub files: Vec<FileHash>,
+}
+
+// 事件内容，kind 区分事件类型
+#[napi(discriminant = "kind")]
+pub enum ClipboardPayload {
+    // files 包括在复制的文件夹和压缩包中找到的文件；truncated 为 true 时有文件夹没有遍历完或压缩包没有列完
+    Files {
+        files: Vec<FileInfo>,
+        directories: Vec<DirectoryInfo>,
+        archives: Vec<ArchiveInfo>,
+        summary: FilesSummary,
+        truncated: bool,
+    },
+    Image { image: ShotInfo },
+    Table { table: TableInfo },
+    Text { text: TextInfo },
+    Code { code: CodeInfo },
+    Cleared,
+    Hashes { hashes: HashesInfo },
+}
+
+// 统一的剪贴板事件
+#[napi(object)]
+pub struct ClipboardEvent {
+    // 单调递增的序号，不连续说明中间有事件丢失
+    pub seq: i64,
+    // Unix 时间戳 (毫秒)
+    pub timestamp: i64,
+    pub pid: u32,
+    pub pname: String,
+    pub selection: Selection,
+    // 本次变化提供的全部格式
+    pub formats: Vec<String>,
+    pub payload: ClipboardPayload,
+}
+
+// clipboard_initialize 的可选配置
+#[napi(object)]
+#[derive(Default)]
+pub struct MonitorOptions {
+    // 要监听的选区，默认只监听 CLIPBOARD；PRIMARY / SECONDARY 仅 X11 (PRIMARY 也支持 Wayland)
+    pub selections: Option<Vec<Selection>>,
+    // 表格事件中最多携带的单元格数量，默认 1000
+    pub max_table_cells: Option<u32>,
+    // 文本事件预览的最大字符数，默认 100，0 表示不带预览
+    pub text_preview_chars: Option<u32>,
+    // 来源路径的包含 / 排除规则，默认不限制
+    pub path_rules: Option<Vec<PathRule>>,
+    // 遍历复制的文件夹的最大深度，默认 16，0 表示不遍历文件夹 (只上报文件夹本身，limit 为 DEPTH)
+    pub max_walk_depth: Option<u32>,
+    // 一次复制中最多遍历的文件数 (全部文件夹合计)，默认 10000
+    pub max_walk_files: Option<u32>,
+    // 一次复制中遍历文件夹的最长时间 (毫秒)，默认 2000
+    pub walk_timeout_ms: Option<u32>,
+    // 列出复制的压缩包时的最大嵌套深度，默认 3，0 表示不列出压缩包
+    pub max_archive_depth: Option<u32>,
+    // 一次复制中最多列出的压缩包条目数 (全部压缩包合计)，默认 10000
+    pub max_archive_entries: Option<u32>,
+    // 一次复制中最多解压的字节数 (全部压缩包合计)，默认 64 MiB；识别条目类型时读取的开头不计入
+    pub max_archive_bytes: Option<i64>,
+    // 为复制的文件计算的哈希，默认不计算；文件事件先投递，哈希在后台线程算完后以 Hashes 事件补发
+    pub hash_algorithms: Option<Vec<HashAlgorithm>>,
+    // 超过这个大小的文件不计算哈希，默认 256 MiB
+    pub max_hash_bytes: Option<i64>,
+    // 没有匹配任何分类的文件也上报，分类为 "OTHER"，默认 false
+    pub report_unmatched: Option<bool>,
+    // 识别复制的代码片段，识别出的文本以 Code 事件代替 Text 事件上报，默认 true
+    pub detect_code: Option<bool>,
+}
+
+// 宿主推送的一个剪贴板格式，format 使用 MIME 类型 (如 text/html、image/png)
+#[napi(object)]
+pub struct ClipboardFormat {
+    pub format: String,
+    pub data: Uint8Array,
+}
+
+// 宿主推送的剪贴板快照，WASI 构建中由浏览器的 copy / paste 监听构造
+#[napi(object)]
+pub struct ClipboardSnapshot {
+    pub formats: Vec<ClipboardFormat>,
+    pub pid: Option<u32>,
+    pub pname: Option<String>,
+    pub selection: Option<Selection>,
+}
+
+// 监听启动后运行中出现的错误，此时监听已经停止
+#[napi(object)]
+pub struct MonitorErrorInfo {
+    pub message: String,
+    // 系统错误码 (Windows 为 HRESULT)，没有时为 0
+    pub code: i32,
+}
+
+// getStatus 的返回值，时间均为 Unix 时间戳 (毫秒)
+#[napi(object)]
+pub struct MonitorStatus {
+    pub running: bool,
+    // 监听线程的系统线程 ID，只有 Windows 和 Linux 提供
+    pub thread_id: Option<u32>,
+    pub started_at: Option<i64>,
+    pub last_event_at: Option<i64>,
+    // 后端通知的剪贴板变化次数
+    pub changes_seen: i64,
+    // 分析后上报的事件数
+    pub events_reported: i64,
+    // 被节流丢弃的变化次数
+    pub changes_throttled: i64,
+    // 与上一张图片大小相同而被过滤的次数
+    pub images_deduplicated: i64,
+    // 没能投递的事件数：JS 回调积压超过 1000 个或已释放，或 watchClipboard 的队列已满
+    // 一个事件无论有几个接收方没收到都只计一次
+    pub events_dropped: i64,
+}
+
+// 要监听的文件分类，按后缀或文件头识别出的 MIME 类型匹配
+#[napi(object)]
+pub struct FileCategory {
+    // 分类名称，上报的 FileInfo.category 即为该名称，不能重复
+    pub name: String,
+    // 后缀，如 ".pdf" 或 "pdf"
+    pub extensions: Option<Vec<String>>,
+    // MIME 类型，支持 "image/*" 形式的通配
+    pub mime_types: Option<Vec<String>>,
+    // 多个分类都匹配时取优先级最高的，相同时取先定义的，默认 0
+    pub priority: Option<i32>,
+}
+
+// 来源路径规则，glob 和 regex 二选一；匹配前路径中的 "\\" 统一替换为 "/"
+// 设置了包含规则时只上报命中任一包含规则的文件，命中排除规则的文件一律不上报
+// 压缩包中的条目按 "压缩包路径/条目路径" 匹配，如 "D:/x.zip/node_modules/a.js"
+#[napi(object)]
+pub struct PathRule {
+    // 如 "~/Documents/Contracts/**"、"**/node_modules"，匹配到目录时目录下的文件都算匹配
+    pub glob: Option<String>,
+    // 在整个路径中查找，如 "^/srv/secure/"
+    pub regex: Option<String>,
+    // 为 true 时是排除规则，默认为包含规则
+    pub exclude: Option<bool>,
+    // 默认与系统一致：Windows / macOS 不区分大小写，其他系统区分
+    pub case_sensitive: Option<bool>,
+}
+
+// updateConfig 的参数，未传入的项保持当前值
+#[napi(object)]
+#[derive(Default)]
+pub struct MonitorConfig {
+    // 整体替换全部分类
+    pub categories: Option<Vec<FileCategory>>,
+    // 整体替换全部路径规则，传入空数组表示不限制
+    pub path_rules: Option<Vec<PathRule>>,
+    pub max_table_cells: Option<u32>,
+    pub text_preview_chars: Option<u32>,
+    pub max_walk_depth: Option<u32>,
+    pub max_walk_files: Option<u32>,
+    pub walk_timeout_ms: Option<u32>,
+    pub max_archive_depth: Option<u32>,
+    pub max_archive_entries: Option<u32>,
+    pub max_archive_bytes: Option<i64>,
+    // 传入空数组表示不再计算哈希
+    pub hash_algorithms: Option<Vec<HashAlgorithm>>,
+    pub max_hash_bytes: Option<i64>,
+    pub report_unmatched: Option<bool>,
+    pub detect_code: Option<bool>,
+}
+
+impl MonitorOptions {
+    pub fn selections(self) -> Vec<aware::Selection> {
+        match self.selections {
+            Some(selections) if !selections.is_empty() => selections.into_iter().map(Into::into).collect(),
+            _ => vec![aware::Selection::CLIPBOARD],
+        }
     }
 }
 
-pub fn report_shot(files: ShotInfo) {
-    if let Some(tsfn) = GLOBAL_REPORT_SHOT.get() {
-        tsfn.call(
-            Ok(files), ThreadsafeFunctionCallMode::NonBlocking);
-    } else {
-        println!("Warning: No report wnd listener registered yet!");
+impl From<aware::Selection> for Selection {
+    fn from(value: aware::Selection) -> Self {
+        match value {
+            aware::Selection::CLIPBOARD => Selection::CLIPBOARD,
+            aware::Selection::PRIMARY => Selection::PRIMARY,
+            aware::Selection::SECONDARY => Selection::SECONDARY,
+        }
     }
 }
 
-fn report_log(msg: String) {
-    if cfg!(debug_assertions) {
-        println!("{}", msg);
-    } else {
-        if let Some(tsfn) = GLOBAL_LOG.get() {
-            tsfn.call(Ok(msg), ThreadsafeFunctionCallMode::NonBlocking);
+impl From<Selection> for aware::Selection {
+    fn from(value: Selection) -> Self {
+        match value {
+            Selection::CLIPBOARD => aware::Selection::CLIPBOARD,
+            Selection::PRIMARY => aware::Selection::PRIMARY,
+            Selection::SECONDARY => aware::Selection::SECONDARY,
+        }
+    }
+}
+
+// 校验 JS 传入的分类，任一分类无效时返回错误
+pub fn to_file_rules(categories: Vec<FileCategory>) -> napi::Result<aware::FileRules> {
+    categories
+        .into_iter()
+        .map(|category| {
+            aware::FileCategory::new(
+                &category.name,
+                &category.extensions.unwrap_or_default(),
+                &category.mime_types.unwrap_or_default(),
+                category.priority.unwrap_or(0),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(aware::FileRules::new)
+        .map_err(to_napi_error)
+}
+
+// 校验 JS 传入的路径规则，任一规则无效时返回错误
+pub fn to_path_rules(rules: Vec<PathRule>) -> napi::Result<aware::PathRules> {
+    let mut path_rules = aware::PathRules::default();
+    for rule in rules {
+        let case_sensitive = rule.case_sensitive.unwrap_or(aware::analysis::pattern::DEFAULT_CASE_SENSITIVE);
+        let pattern = match (rule.glob, rule.regex) {
+            (Some(glob), None) => aware::PathPattern::glob(&glob, case_sensitive),
+            (None, Some(regex)) => aware::PathPattern::regex(&regex, case_sensitive),
+            _ => return Err(napi::Error::new(Status::InvalidArg, "A path rule needs exactly one of glob or regex")),
+        }
+        .map_err(to_napi_error)?;
+        if rule.exclude.unwrap_or(false) {
+            path_rules.exclude.push(pattern);
         } else {
-            println!("Warning: No report log listener registered yet!");
+            path_rules.include.push(pattern);
         }
     }
+    Ok(path_rules)
 }
 
-#[doc(hidden)]
-pub(crate) fn report_error(msg: fmt::Arguments) {
-  let log_msg = format!("[clipboard_error]: {}", msg);
-  report_log(log_msg);
+impl From<aware::WalkLimit> for WalkLimit {
+    fn from(value: aware::WalkLimit) -> Self {
+        match value {
+            aware::WalkLimit::DEPTH => WalkLimit::DEPTH,
+            aware::WalkLimit::FILES => WalkLimit::FILES,
+            aware::WalkLimit::TIME => WalkLimit::TIME,
+        }
+    }
+}
+
+impl From<aware::DirectoryInfo> for DirectoryInfo {
+    fn from(value: aware::DirectoryInfo) -> Self {
+        Self {
+            path: value.path,
+            files: value.files as u32,
+            categories: value.categories.into_iter().map(|(category, count)| (category, count as u32)).collect(),
+            limit: value.limit.map(Into::into),
+        }
+    }
+}
+
+impl From<aware::FilesSummary> for FilesSummary {
+    fn from(value: aware::FilesSummary) -> Self {
+        Self {
+            files: value.files as u32,
+            bytes: value.bytes as i64,
+            categories: value
+                .categories
+                .into_iter()
+                .map(|(category, summary)| {
+                    (category, CategorySummary { files: summary.files as u32, bytes: summary.bytes as i64 })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<aware::CodeLanguage> for CodeLanguage {
+    fn from(value: aware::CodeLanguage) -> Self {
+        match value {
+            aware::CodeLanguage::RUST => CodeLanguage::RUST,
+            aware::CodeLanguage::C => CodeLanguage::C,
+            aware::CodeLanguage::CPP => CodeLanguage::CPP,
+            aware::CodeLanguage::CSHARP => CodeLanguage::CSHARP,
+            aware::CodeLanguage::JAVA => CodeLanguage::JAVA,
+            aware::CodeLanguage::GO => CodeLanguage::GO,
+            aware::CodeLanguage::PYTHON => CodeLanguage::PYTHON,
+            aware::CodeLanguage::JAVASCRIPT => CodeLanguage::JAVASCRIPT,
+            aware::CodeLanguage::TYPESCRIPT => CodeLanguage::TYPESCRIPT,
+            aware::CodeLanguage::SQL => CodeLanguage::SQL,
+            aware::CodeLanguage::SHELL => CodeLanguage::SHELL,
+        }
+    }
+}
+
+impl From<aware::ArchiveFormat> for ArchiveFormat {
+    fn from(value: aware::ArchiveFormat) -> Self {
+        match value {
+            aware::ArchiveFormat::ZIP => ArchiveFormat::ZIP,
+            aware::ArchiveFormat::TAR => ArchiveFormat::TAR,
+            aware::ArchiveFormat::TARGZ => ArchiveFormat::TARGZ,
+            aware::ArchiveFormat::SEVENZIP => ArchiveFormat::SEVENZIP,
+        }
+    }
+}
+
+impl From<aware::ArchiveLimit> for ArchiveLimit {
+    fn from(value: aware::ArchiveLimit) -> Self {
+        match value {
+            aware::ArchiveLimit::DEPTH => ArchiveLimit::DEPTH,
+            aware::ArchiveLimit::ENTRIES => ArchiveLimit::ENTRIES,
+            aware::ArchiveLimit::BYTES => ArchiveLimit::BYTES,
+            aware::ArchiveLimit::TIME => ArchiveLimit::TIME,
+        }
+    }
+}
+
+impl From<aware::ArchiveInfo> for ArchiveInfo {
+    fn from(value: aware::ArchiveInfo) -> Self {
+        Self {
+            path: value.path,
+            format: value.format.into(),
+            entries: value.entries as u32,
+            categories: value.categories.into_iter().map(|(category, count)| (category, count as u32)).collect(),
+            limit: value.limit.map(Into::into),
+            error: value.error,
+        }
+    }
+}
+
+impl From<aware::FileInfo> for FileInfo {
+    fn from(value: aware::FileInfo) -> Self {
+        Self {
+            name: value.name,
+            path: value.path,
+            category: value.category,
+            extension: value.extension,
+            mime_type: value.mime_type,
+            extension_mismatch: value.extension_mismatch,
+            directory: value.directory,
+            archive_entry: value.archive_entry,
+            size: value.metadata.size.map(|size| size as i64),
+            modified_at: value.metadata.modified_at.map(|time| time as i64),
+            created_at: value.metadata.created_at.map(|time| time as i64),
+            uid: value.metadata.uid,
+            permissions: value.metadata.permissions,
+            readonly: value.metadata.readonly,
+            pname: value.pname,
+            pid: value.pid,
+            selection: value.selection.into(),
+        }
+    }
+}
+
+impl From<aware::ShotInfo> for ShotInfo {
+    fn from(value: aware::ShotInfo) -> Self {
+        Self {
+            pname: value.pname,
+            pid: value.pid,
+            data: value.data.into(),
+            selection: value.selection.into(),
+        }
+    }
+}
+
+impl From<aware::TableInfo> for TableInfo {
+    fn from(value: aware::TableInfo) -> Self {
+        Self {
+            pname: value.pname,
+            pid: value.pid,
+            format: value.format,
+            selection: value.selection.into(),
+            rows: value.rows as u32,
+            columns: value.columns as u32,
+            cells: value.cells,
+            truncated: value.truncated,
+        }
+    }
+}
+
+impl From<aware::TextInfo> for TextInfo {
+    fn from(value: aware::TextInfo) -> Self {
+        Self {
+            pname: value.pname,
+            pid: value.pid,
+            format: value.format,
+            selection: value.selection.into(),
+            chars: value.chars as u32,
+            bytes: value.bytes as u32,
+            lines: value.lines as u32,
+            preview: value.preview,
+            hash: value.hash,
+        }
+    }
 }
 
-#[doc(hidden)]
-pub(crate) fn report_info(msg: fmt::Arguments) {
-  let log_msg = format!("[clipboard_info]: {}", msg);
-  report_log(log_msg);
+impl From<aware::CodeInfo> for CodeInfo {
+    fn from(value: aware::CodeInfo) -> Self {
+        Self {
+            text: value.text.into(),
+            language: value.language.into(),
+            confidence: value.confidence,
+        }
+    }
 }
 
-#[macro_export]
-macro_rules! report_error_log {
-    // format_args! 是编译器内置宏，它不分配内存，只打包参数
-    ($($arg:tt)*) => {
-        $crate::global::report_error(format_args!($($arg)*))
+impl From<aware::FileHash> for FileHash {
+    fn from(value: aware::FileHash) -> Self {
+        Self {
+            path: value.path,
+            category: value.category,
+            sha256: value.sha256,
+            blake3: value.blake3,
+        }
     }
 }
 
-#[macro_export]
-macro_rules! report_info_log {
-    // format_args! 是编译器内置宏，它不分配内存，只打包参数
-    ($($arg:tt)*) => {
-        $crate::global::report_info(format_args!($($arg)*))
+impl From<aware::HashesInfo> for HashesInfo {
+    fn from(value: aware::HashesInfo) -> Self {
+        Self {
+            event_seq: value.event_seq as i64,
+            files: value.files.into_iter().map(Into::into).collect(),
+        }
     }
 }
 
-pub fn get_code_extensions() -> &'static HashSet<String> {
-    CODE_EXTENSIONS.get_or_init(|| {
-        HashSet::from(
-            [
-                ".cpp", ".h", ".hpp", ".c", ".cs", ".py", ".java", ".js", ".ts", 
-                ".html", ".css", ".json", ".xml", ".sql", ".go", ".rs",
-            ].map(String::from)
-        )
-    })
+impl From<aware::ClipboardReport> for ClipboardPayload {
+    fn from(value: aware::ClipboardReport) -> Self {
+        match value {
+            aware::ClipboardReport::Files(files) => Self::Files {
+                truncated: files.truncated(),
+                summary: files.summary().into(),
+                files: files.files.into_iter().map(Into::into).collect(),
+                directories: files.directories.into_iter().map(Into::into).collect(),
+                archives: files.archives.into_iter().map(Into::into).collect(),
+            },
+            aware::ClipboardReport::Shot(shot) => Self::Image { image: shot.into() },
+            aware::ClipboardReport::Table(table) => Self::Table { table: table.into() },
+            aware::ClipboardReport::Text(text) => Self::Text { text: text.into() },
+            aware::ClipboardReport::Code(code) => Self::Code { code: code.into() },
+            aware::ClipboardReport::Cleared => Self::Cleared,
+            aware::ClipboardReport::Hashes(hashes) => Self::Hashes { hashes: hashes.into() },
+        }
+    }
 }
 
-pub fn get_image_extensions() -> &'static HashSet<String> {
-    IMAG_EXTENSIONS.get_or_init(|| {
-        HashSet::from(
-            [
-                ".jpg", ".jpeg", ".png", ".bmp", ".gif", ".ico", ".tiff", ".webp",
-            ].map(String::from)
-        )
-    })
+impl From<aware::ClipboardEvent> for ClipboardEvent {
+    fn from(value: aware::ClipboardEvent) -> Self {
+        Self {
+            seq: value.seq as i64,
+            timestamp: value.timestamp as i64,
+            pid: value.pid,
+            pname: value.pname,
+            selection: value.selection.into(),
+            formats: value.formats,
+            payload: value.report.into(),
+        }
+    }
 }
 
-pub fn get_excel_extensions() -> &'static HashSet<String> {
-    EXCE_EXTENSIONS.get_or_init(|| {
-        HashSet::from(
-            [
-                ".xls", ".xlsx", ".csv", ".xlsm",
-            ].map(String::from)
-        )
-    })
+impl From<ClipboardSnapshot> for aware::analysis::mock::MockSnapshot {
+    fn from(value: ClipboardSnapshot) -> Self {
+        let mut snapshot = Self::new();
+        for format in value.formats {
+            snapshot = snapshot.with_format(&format.format, format.data.to_vec());
+        }
+        if value.pid.is_some() || value.pname.is_some() {
+            snapshot = snapshot.with_owner(value.pid.unwrap_or(0), value.pname.as_deref().unwrap_or("Unknown"));
+        }
+        if let Some(selection) = value.selection {
+            snapshot = snapshot.with_selection(selection.into());
+        }
+        snapshot
+    }
+}
+
+impl From<aware::stats::MonitorStats> for MonitorStatus {
+    fn from(value: aware::stats::MonitorStats) -> Self {
+        let time = |millis: u64| (millis != 0).then_some(millis as i64);
+        Self {
+            running: value.running,
+            thread_id: (value.thread_id != 0).then_some(value.thread_id),
+            started_at: time(value.started_at),
+            last_event_at: time(value.last_event_at),
+            changes_seen: value.changes_seen as i64,
+            events_reported: value.events_reported as i64,
+            changes_throttled: value.changes_throttled as i64,
+            images_deduplicated: value.images_deduplicated as i64,
+            events_dropped: value.events_dropped as i64,
+        }
+    }
+}
+
+impl From<aware::MonitorError> for MonitorErrorInfo {
+    fn from(value: aware::MonitorError) -> Self {
+        Self {
+            message: value.message,
+            code: value.code,
+        }
+    }
+}
+
+// 没有系统错误码时只保留错误信息
+pub fn to_napi_error(e: aware::MonitorError) -> napi::Error {
+    let message = if e.code != 0 { e.to_string() } else { e.message };
+    napi::Error::new(Status::GenericFailure, message)
+}
+
+// 构造 JS 错误对象并附上系统错误码 errno，用于 Promise 的 reject
+pub fn monitor_error_value(env: &Env, e: aware::MonitorError) -> napi::Error {
+    let code = e.code;
+    let error = env.create_error(to_napi_error(e)).and_then(|mut error| {
+        error.set_named_property("errno", code)?;
+        Ok(error)
+    });
+    match error {
+        Ok(error) => error.to_unknown().into(),
+        Err(e) => e,
+    }
+}
+
+// 注册 JS 回调，已有回调时报错
+pub fn register_listener<T>(slot: &Mutex<Option<T>>, listener: T, name: &str) -> napi::Result<()> {
+    let mut guard = slot.lock().unwrap();
+    if guard.is_some() {
+        return Err(napi::Error::new(
+            napi::Status::GenericFailure,
+            format!("Global {} listener already registered", name),
+        ));
+    }
+    *guard = Some(listener);
+    Ok(())
+}
+
+// 释放 clipboardInitialize 注册的回调
+pub fn release_monitor_listeners() {
+    GLOBAL_REPORT.lock().unwrap().take();
+    GLOBAL_REPORT_SHOT.lock().unwrap().take();
+    GLOBAL_LOG.lock().unwrap().take();
+}
+
+// 释放全部 JS 回调
+pub fn release_listeners() {
+    release_monitor_listeners();
+    GLOBAL_EVENT.lock().unwrap().take();
+    GLOBAL_ERROR.lock().unwrap().take();
+    crate::subscription::release_subscribers();
+}
+
+// 非阻塞地调用 JS 回调，回调队列已满或已关闭时返回 false
+pub fn notify<T: ToNapiValue + 'static>(tsfn: &EventCallback<T>, value: T) -> bool {
+    tsfn.call(Ok(value), ThreadsafeFunctionCallMode::NonBlocking) == Status::Ok
+}
+
+pub fn report_file(files: Vec<FileInfo>) -> bool {
+    if let Some(tsfn) = GLOBAL_REPORT.lock().unwrap().as_ref() {
+        notify(tsfn, files)
+    } else {
+        println!("Warning: No report wnd listener registered yet!");
+        true
+    }
+}
+
+pub fn report_shot(files: ShotInfo) -> bool {
+    if let Some(tsfn) = GLOBAL_REPORT_SHOT.lock().unwrap().as_ref() {
+        notify(tsfn, files)
+    } else {
+        println!("Warning: No report wnd listener registered yet!");
+        true
+    }
+}
+
+// 把 core 的分析结果转换为 JS 对象并上报
+// 文件和图片同时保留原有的 report_file / report_shot 回调
+// 任一接收方没收到时计一次丢弃
+pub fn report(event: aware::ClipboardEvent) {
+    let mut delivered = match &event.report {
+        aware::ClipboardReport::Files(files) if !files.files.is_empty() => {
+            report_file(files.files.iter().cloned().map(Into::into).collect())
+        }
+        aware::ClipboardReport::Shot(shot) => report_shot(shot.clone().into()),
+        _ => true,
+    };
+    delivered &= crate::subscription::dispatch(&event);
+    delivered &= crate::watcher::dispatch(&event);
+    if let Some(tsfn) = GLOBAL_EVENT.lock().unwrap().as_ref() {
+        delivered &= notify(tsfn, event.into());
+    }
+    if !delivered {
+        aware::stats::record_dropped();
+    }
+}
+
+// 监听运行中失败：交给 JS 的 error 回调，并结束全部 watchClipboard 迭代器
+pub fn report_monitor_error(e: aware::MonitorError) {
+    crate::watcher::fail_watchers(&e);
+    if let Some(tsfn) = GLOBAL_ERROR.lock().unwrap().as_ref() {
+        tsfn.call(Ok(e.into()), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+// 开发模式直接打印，生产模式转发给 JS 的 log 回调
+pub fn report_log(msg: String) {
+    if cfg!(debug_assertions) {
+        println!("{}", msg);
+    } else if let Some(tsfn) = GLOBAL_LOG.lock().unwrap().as_ref() {
+        tsfn.call(Ok(msg), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monitor_status_maps_zero_to_none() {
+        let stats = aware::stats::MonitorStats {
+            running: true,
+            thread_id: 42,
+            started_at: 1_700_000_000_005,
+            last_event_at: 0,
+            changes_seen: 12,
+            events_reported: 9,
+            changes_throttled: 2,
+            images_deduplicated: 1,
+            events_dropped: 3,
+        };
+        let status = MonitorStatus::from(stats);
+        assert!(status.running);
+        assert_eq!(status.thread_id, Some(42));
+        assert_eq!(status.started_at, Some(1_700_000_000_005));
+        assert_eq!(status.last_event_at, None);
+        assert_eq!(
+            (status.changes_seen, status.events_reported, status.changes_throttled, status.images_deduplicated, status.events_dropped),
+            (12, 9, 2, 1, 3)
+        );
+
+        let stopped = MonitorStatus::from(aware::stats::MonitorStats::default());
+        assert!(!stopped.running);
+        assert_eq!((stopped.thread_id, stopped.started_at, stopped.last_event_at), (None, None, None));
+    }
 }
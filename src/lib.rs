@@ -1,64 +1,120 @@
 #![deny(clippy::all)]
 pub mod global;
-mod utils;
-mod hooks;
-mod analysis;
+pub mod subscription;
+pub mod watcher;
 
 use napi_derive::napi;
-use napi::{ Env, Status };
+use napi::{ Env, JsDeferred, Status };
+use napi::bindgen_prelude::{Object, Unknown};
 use napi::threadsafe_function::{ThreadsafeFunction};
 
-use std::collections::HashSet;
-use std::sync::atomic::Ordering;
+use std::any::Any;
 use std::ffi::c_void;
-use std::thread;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use windows::{
-    Win32::Foundation::{WPARAM, LPARAM},
-    Win32::System::Threading::GetCurrentThreadId,
-    Win32::UI::WindowsAndMessaging::{ 
-        PostThreadMessageW, WM_QUIT
+use clipboard_copy_aware_core::{
+    analysis::host::push_snapshot,
+    global::{
+        set_file_rules, set_log_sink, set_path_rules, DETECT_CODE, HASH_BLAKE3, HASH_SHA256, MAX_ARCHIVE_BYTES, MAX_ARCHIVE_DEPTH,
+        MAX_ARCHIVE_ENTRIES, MAX_HASH_BYTES, MAX_TABLE_CELLS, MAX_WALK_DEPTH, MAX_WALK_FILES, REPORT_UNMATCHED,
+        TEXT_PREVIEW_CHARS, WALK_TIMEOUT_MS,
     },
+    report_error_log, report_info_log, setup_clipboard_monitor, stats::monitor_stats, stop_monitor, MonitorError, Selection,
 };
 
 use crate::global::{
-   FileInfo, ShotInfo, CODE_EXTENSIONS, EXCE_EXTENSIONS, GLOBAL_LOG, GLOBAL_REPORT, GLOBAL_REPORT_SHOT, IMAG_EXTENSIONS, MONITOR_THREAD_ID,
+   monitor_error_value, register_listener, release_listeners, release_monitor_listeners, report, report_log,
+   report_monitor_error, to_file_rules, to_napi_error, to_path_rules, ClipboardEvent, ClipboardSnapshot,
+   FileCategory, FileInfo, HashAlgorithm, MonitorConfig, MonitorErrorInfo, MonitorOptions, MonitorStatus, ShotInfo, EVENT_QUEUE_SIZE, GLOBAL_ERROR,
+   GLOBAL_EVENT, GLOBAL_LOG, GLOBAL_REPORT, GLOBAL_REPORT_SHOT,
 };
-use crate::hooks::setup_clipboard_monitor;
+use crate::watcher::close_watchers;
+
+// 后台监听线程，clipboard_shutdown 时等待其退出
+static MONITOR_HANDLE: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+// env 清理钩子只注册一次，重复注册同一钩子会导致 Node 崩溃
+static CLEANUP_HOOK_ADDED: AtomicBool = AtomicBool::new(false);
+// 等待监听线程退出的最长时间
+const SHUTDOWN_TIMEOUT_MS: u64 = 5000;
 
 unsafe extern "C" fn cleanup_monitor_thread(_arg: *mut c_void) {
-    let thread_id = MONITOR_THREAD_ID.load(Ordering::SeqCst);
-    if thread_id != 0 {
-        // 向后台线程发送 WM_QUIT，打破它的死循环
-        let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
-        println!("Cleanup hook triggered: Sent WM_QUIT to monitor thread.");
+    // 通知后台线程退出，打破它的死循环
+    match stop_monitor_thread() {
+        Ok(()) => println!("Cleanup hook triggered: Stopped monitor thread."),
+        Err(e) => println!("Cleanup hook triggered: {}", e),
     }
 }
 
-#[napi]
-pub fn clipboard_initialize(
-    code_exts: Vec<String>, 
-    imag_exts: Vec<String>, 
-    exce_exts: Vec<String>, 
-    mut report_file: ThreadsafeFunction<Vec<FileInfo>>,
-    mut report_shot: ThreadsafeFunction<ShotInfo>,
+// 通知监听线程退出并等待
+// 线程可能还没进入等待状态 (如窗口尚未创建)，因此重复发送停止信号直到它退出
+fn stop_monitor_thread() -> napi::Result<()> {
+    let mut guard = MONITOR_HANDLE.lock().unwrap();
+    let Some(handle) = guard.take() else {
+        return Ok(());
+    };
+
+    let deadline = Instant::now() + Duration::from_millis(SHUTDOWN_TIMEOUT_MS);
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            // 保留句柄，之后可以再次尝试
+            *guard = Some(handle);
+            return Err(napi::Error::new(Status::GenericFailure, "Clipboard monitor thread did not exit in time"));
+        }
+        stop_monitor();
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = handle.join();
+    Ok(())
+}
+
+type Startup = JsDeferred<(), Box<dyn FnOnce(Env) -> napi::Result<()> + Send>>;
+
+// 启动剪贴板监听，返回的 Promise 在监听真正安装后 resolve
+// 启动失败时 reject，错误对象的 errno 为系统错误码；参数无效或已在运行时直接抛出
+// categories 为要监听的文件分类，复制的文件匹配任一分类时通过 reportFile 上报
+#[napi(ts_return_type = "Promise<void>")]
+pub fn clipboard_initialize<'env>(
+    categories: Vec<FileCategory>,
+    mut report_file: ThreadsafeFunction<Vec<FileInfo>, Unknown<'static>, Vec<FileInfo>, Status, true, false, EVENT_QUEUE_SIZE>,
+    mut report_shot: ThreadsafeFunction<ShotInfo, Unknown<'static>, ShotInfo, Status, true, false, EVENT_QUEUE_SIZE>,
     mut log: ThreadsafeFunction<String>,
-    env: Env
-) -> napi::Result<()> {
+    options: Option<MonitorOptions>,
+    env: &'env Env
+) -> napi::Result<Object<'env>> {
+    {
+        let mut handle = MONITOR_HANDLE.lock().unwrap();
+        // 上一次启动失败或运行中失败时线程已经退出，释放它的回调后可以直接重新初始化
+        if let Some(finished) = handle.take_if(|handle| handle.is_finished()) {
+            let _ = finished.join();
+            release_monitor_listeners();
+        }
+        if handle.is_some() {
+            return Err(napi::Error::new(Status::GenericFailure, "Clipboard monitor is already running, call clipboardShutdown first"));
+        }
+    }
+    // 先校验分类和路径规则，无效时不注册任何回调
+    let rules = to_file_rules(categories)?;
+    let mut options = options.unwrap_or_default();
+    let path_rules = to_path_rules(options.path_rules.take().unwrap_or_default())?;
+
     #[allow(deprecated)]
-    report_file.unref(&env)?;
+    report_file.unref(env)?;
     #[allow(deprecated)]
-    report_shot.unref(&env)?;
+    report_shot.unref(env)?;
     #[allow(deprecated)]
-    log.unref(&env)?;
+    log.unref(env)?;
 
-    CODE_EXTENSIONS.get_or_init(|| {HashSet::<String>::from_iter(code_exts)});
-    IMAG_EXTENSIONS.get_or_init(|| {HashSet::<String>::from_iter(imag_exts)});
-    EXCE_EXTENSIONS.get_or_init(|| {HashSet::<String>::from_iter(exce_exts)});
+    set_file_rules(rules);
+    set_path_rules(path_rules);
 
-    GLOBAL_REPORT.set(report_file).map_err(|_| napi::Error::new(Status::GenericFailure, "Global report file listener already registered"))?;
-    GLOBAL_REPORT_SHOT.set(report_shot).map_err(|_| napi::Error::new(Status::GenericFailure, "Global report shot listener already registered"))?;
-    GLOBAL_LOG.set(log).map_err(|_| napi::Error::new(Status::GenericFailure, "Global log listener already registered"))?;
+    register_listener(&GLOBAL_REPORT, report_file, "report file")?;
+    register_listener(&GLOBAL_REPORT_SHOT, report_shot, "report shot")?;
+    register_listener(&GLOBAL_LOG, log, "log")?;
+    let _ = set_log_sink(Box::new(report_log));
 
     if cfg!(debug_assertions) {
         report_info_log!("[Debug] 当前正处于开发模式运行，开启详细日志...");
@@ -66,24 +122,190 @@ pub fn clipboard_initialize(
         report_info_log!("[Release] 生产模式运行");
     }
 
-    env.add_env_cleanup_hook(
-        std::ptr::null_mut(), 
-        |arg| unsafe { cleanup_monitor_thread(arg) }
-    )?;
-
-    thread::spawn(move || {
-        unsafe {
-            let thread_id = GetCurrentThreadId();
-            MONITOR_THREAD_ID.store(thread_id, Ordering::SeqCst);
-            let _ = setup_clipboard_monitor().map_err(|e| {
-                napi::Error::from_reason(format!(
-                    "Clipboard Monitor Failed: {} (Code: 0x{:X})", 
-                    e.message(), 
-                    e.code().0
-                ))
-            });
+    apply_limits(&MonitorConfig {
+        max_table_cells: options.max_table_cells,
+        text_preview_chars: options.text_preview_chars,
+        max_walk_depth: options.max_walk_depth,
+        max_walk_files: options.max_walk_files,
+        walk_timeout_ms: options.walk_timeout_ms,
+        max_archive_depth: options.max_archive_depth,
+        max_archive_entries: options.max_archive_entries,
+        max_archive_bytes: options.max_archive_bytes,
+        hash_algorithms: options.hash_algorithms.take(),
+        max_hash_bytes: options.max_hash_bytes,
+        report_unmatched: options.report_unmatched,
+        detect_code: options.detect_code,
+        ..Default::default()
+    });
+    let (startup, promise) = env.create_deferred()?;
+    spawn_monitor(env, options.selections(), startup)?;
+    Ok(promise)
+}
+
+// 写入配置中传入的各项上限和开关
+fn apply_limits(config: &MonitorConfig) {
+    if let Some(max_cells) = config.max_table_cells {
+        MAX_TABLE_CELLS.store(max_cells as usize, Ordering::Relaxed);
+    }
+    if let Some(preview_chars) = config.text_preview_chars {
+        TEXT_PREVIEW_CHARS.store(preview_chars as usize, Ordering::Relaxed);
+    }
+    if let Some(depth) = config.max_walk_depth {
+        MAX_WALK_DEPTH.store(depth as usize, Ordering::Relaxed);
+    }
+    if let Some(files) = config.max_walk_files {
+        MAX_WALK_FILES.store(files as usize, Ordering::Relaxed);
+    }
+    if let Some(timeout) = config.walk_timeout_ms {
+        WALK_TIMEOUT_MS.store(timeout as u64, Ordering::Relaxed);
+    }
+    if let Some(depth) = config.max_archive_depth {
+        MAX_ARCHIVE_DEPTH.store(depth as usize, Ordering::Relaxed);
+    }
+    if let Some(entries) = config.max_archive_entries {
+        MAX_ARCHIVE_ENTRIES.store(entries as usize, Ordering::Relaxed);
+    }
+    if let Some(bytes) = config.max_archive_bytes {
+        MAX_ARCHIVE_BYTES.store(bytes.max(0) as u64, Ordering::Relaxed);
+    }
+    if let Some(algorithms) = &config.hash_algorithms {
+        HASH_SHA256.store(algorithms.contains(&HashAlgorithm::SHA256), Ordering::Relaxed);
+        HASH_BLAKE3.store(algorithms.contains(&HashAlgorithm::BLAKE3), Ordering::Relaxed);
+    }
+    if let Some(bytes) = config.max_hash_bytes {
+        MAX_HASH_BYTES.store(bytes.max(0) as u64, Ordering::Relaxed);
+    }
+    if let Some(report_unmatched) = config.report_unmatched {
+        REPORT_UNMATCHED.store(report_unmatched, Ordering::Relaxed);
+    }
+    if let Some(detect_code) = config.detect_code {
+        DETECT_CODE.store(detect_code, Ordering::Relaxed);
+    }
+}
+
+// 运行中更新配置，未传入的项保持不变
+// 分类和路径规则各自整体替换，任一规则无效时报错且所有配置都不生效
+#[napi]
+pub fn update_config(mut config: MonitorConfig) -> napi::Result<()> {
+    let rules = config.categories.take().map(to_file_rules).transpose()?;
+    let path_rules = config.path_rules.take().map(to_path_rules).transpose()?;
+    if let Some(rules) = rules {
+        set_file_rules(rules);
+    }
+    if let Some(path_rules) = path_rules {
+        set_path_rules(path_rules);
+    }
+    apply_limits(&config);
+    report_info_log!("Clipboard monitor config updated.");
+    Ok(())
+}
+
+// 订阅统一的剪贴板事件流 (文件、图片、表格、文本、代码、清空)
+#[napi]
+pub fn clipboard_on_event(mut callback: ThreadsafeFunction<ClipboardEvent, Unknown<'static>, ClipboardEvent, Status, true, false, EVENT_QUEUE_SIZE>, env: Env) -> napi::Result<()> {
+    #[allow(deprecated)]
+    callback.unref(&env)?;
+    register_listener(&GLOBAL_EVENT, callback, "event")
+}
+
+// 订阅监听运行中的错误 (如显示服务器连接断开)，出现错误时监听已停止，
+// 需要 clipboardShutdown 后重新 clipboardInitialize
+#[napi]
+pub fn clipboard_on_error(mut callback: ThreadsafeFunction<MonitorErrorInfo>, env: Env) -> napi::Result<()> {
+    #[allow(deprecated)]
+    callback.unref(&env)?;
+    register_listener(&GLOBAL_ERROR, callback, "error")
+}
+
+// 停止监听线程并等待其退出，释放全部 JS 回调，之后可以再次调用 clipboardInitialize
+#[napi]
+pub fn clipboard_shutdown() -> napi::Result<()> {
+    let result = stop_monitor_thread();
+    release_listeners();
+    close_watchers();
+    report_info_log!("Clipboard monitor shut down.");
+    result
+}
+
+fn spawn_monitor(env: &Env, selections: Vec<Selection>, startup: Startup) -> napi::Result<()> {
+    if !CLEANUP_HOOK_ADDED.swap(true, Ordering::SeqCst) {
+        env.add_env_cleanup_hook(
+            std::ptr::null_mut(),
+            |arg| unsafe { cleanup_monitor_thread(arg) }
+        )?;
+    }
+
+    let handle = thread::spawn(move || {
+        let mut startup = Some(startup);
+        // 监听线程 panic 时同样要结束 Promise，否则 JS 侧会一直等待
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            setup_clipboard_monitor(
+                &selections,
+                || {
+                    if let Some(startup) = startup.take() {
+                        startup.resolve(Box::new(|_| Ok(())));
+                    }
+                },
+                report,
+            )
+        }))
+        .unwrap_or_else(|payload| Err(MonitorError::new(format!("Clipboard monitor panicked: {}", panic_message(&*payload)))));
+        if let Err(e) = result {
+            report_error_log!("Clipboard Monitor Failed: {}", e);
+            // 还没有安装成功的是启动失败，交给 Promise；否则走 error 回调
+            match startup.take() {
+                Some(startup) => startup.resolve(Box::new(move |env| Err(monitor_error_value(&env, e)))),
+                None => report_monitor_error(e),
+            }
         }
     });
+    *MONITOR_HANDLE.lock().unwrap() = Some(handle);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// panic 的参数通常是 &str 或 String
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+// 监听运行状态和计数器
+#[napi]
+pub fn get_status() -> MonitorStatus {
+    monitor_stats().into()
+}
+
+// 以 Prometheus 文本格式输出监听指标，可直接作为 /metrics 的响应内容
+#[napi]
+pub fn get_prometheus_metrics() -> String {
+    monitor_stats().to_prometheus()
+}
+
+// 宿主推送剪贴板内容，只有没有系统剪贴板的构建 (WASI) 会消费这些快照
+#[napi]
+pub fn clipboard_push_snapshot(snapshot: ClipboardSnapshot) -> napi::Result<()> {
+    push_snapshot(snapshot.into()).map_err(to_napi_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_messages_are_extracted() {
+        let message = |f: fn()| {
+            let payload = panic::catch_unwind(f).unwrap_err();
+            panic_message(&*payload).to_string()
+        };
+        let literal = message(|| panic!("backend failed"));
+        let formatted = message(|| panic!("backend failed: {}", 7));
+        let other = message(|| std::panic::panic_any(7u32));
+        assert_eq!(literal, "backend failed");
+        assert_eq!(formatted, "backend failed: 7");
+        assert_eq!(other, "unknown panic");
+    }
+}
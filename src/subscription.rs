@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use napi_derive::napi;
+use napi::bindgen_prelude::Unknown;
+use napi::threadsafe_function::ThreadsafeFunction;
+use napi::{Env, Status};
+
+use clipboard_copy_aware_core as aware;
+
+use crate::global::{notify, ClipboardEvent, EventCallback, EVENT_QUEUE_SIZE};
+
+// subscribe 注册的回调，每个订阅者各自带过滤条件
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(vec![]);
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Subscriber {
+    id: u64,
+    filter: aware::EventFilter,
+    callback: EventCallback<ClipboardEvent>,
+}
+
+// 事件类型，与 ClipboardPayload 的 kind 对应
+#[napi]
+#[derive(Debug)]
+pub enum ClipboardEventKind {
+    FILES,
+    IMAGE,
+    TABLE,
+    TEXT,
+    CLEARED,
+    CODE,
+    HASHES
+}
+
+// 订阅的过滤条件，未传入或为空数组表示不限制，多个条件同时满足才会收到事件
+#[napi(object)]
+#[derive(Default)]
+pub struct SubscriptionFilter {
+    pub kinds: Option<Vec<ClipboardEventKind>>,
+    // 只保留这些分类 (名称) 的文件，设置后只会收到文件事件
+    pub categories: Option<Vec<String>>,
+    // 来源进程名，不区分大小写
+    pub pnames: Option<Vec<String>>,
+}
+
+impl From<ClipboardEventKind> for aware::EventKind {
+    fn from(value: ClipboardEventKind) -> Self {
+        match value {
+            ClipboardEventKind::FILES => aware::EventKind::FILES,
+            ClipboardEventKind::IMAGE => aware::EventKind::IMAGE,
+            ClipboardEventKind::TABLE => aware::EventKind::TABLE,
+            ClipboardEventKind::TEXT => aware::EventKind::TEXT,
+            ClipboardEventKind::CODE => aware::EventKind::CODE,
+            ClipboardEventKind::HASHES => aware::EventKind::HASHES,
+            ClipboardEventKind::CLEARED => aware::EventKind::CLEARED,
+        }
+    }
+}
+
+impl From<SubscriptionFilter> for aware::EventFilter {
+    fn from(value: SubscriptionFilter) -> Self {
+        Self {
+            kinds: value.kinds.unwrap_or_default().into_iter().map(Into::into).collect(),
+            categories: value.categories.unwrap_or_default(),
+            pnames: value.pnames.unwrap_or_default(),
+        }
+    }
+}
+
+// subscribe 返回的句柄
+#[napi]
+pub struct Subscription {
+    id: u64,
+}
+
+#[napi]
+impl Subscription {
+    // 取消订阅，重复调用无影响
+    #[napi]
+    pub fn unsubscribe(&self) {
+        SUBSCRIBERS.lock().unwrap().retain(|subscriber| subscriber.id != self.id);
+    }
+
+    // 是否仍在订阅 (clipboardShutdown 会清除全部订阅)
+    #[napi(getter)]
+    pub fn active(&self) -> bool {
+        SUBSCRIBERS.lock().unwrap().iter().any(|subscriber| subscriber.id == self.id)
+    }
+}
+
+// 订阅剪贴板事件，可以有任意多个订阅者，各自只收到匹配过滤条件的事件
+#[napi]
+pub fn subscribe(
+    filter: Option<SubscriptionFilter>,
+    mut callback: ThreadsafeFunction<ClipboardEvent, Unknown<'static>, ClipboardEvent, Status, true, false, EVENT_QUEUE_SIZE>,
+    env: Env,
+) -> napi::Result<Subscription> {
+    #[allow(deprecated)]
+    callback.unref(&env)?;
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed);
+    SUBSCRIBERS.lock().unwrap().push(Subscriber {
+        id,
+        filter: filter.unwrap_or_default().into(),
+        callback,
+    });
+    Ok(Subscription { id })
+}
+
+// 把事件推给匹配的订阅者，有订阅者没收到时返回 false
+pub fn dispatch(event: &aware::ClipboardEvent) -> bool {
+    let mut delivered = true;
+    for subscriber in SUBSCRIBERS.lock().unwrap().iter() {
+        if let Some(event) = subscriber.filter.apply(event) {
+            delivered &= notify(&subscriber.callback, event.into());
+        }
+    }
+    delivered
+}
+
+// 释放全部订阅
+pub fn release_subscribers() {
+    SUBSCRIBERS.lock().unwrap().clear();
+}
@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use napi_derive::napi;
+use napi::bindgen_prelude::{Function, JavaScriptClassExt, JsObjectValue, Object, Unknown};
+use napi::{sys, Env, JsDeferred};
+
+use clipboard_copy_aware_core as aware;
+
+use crate::global::{monitor_error_value, ClipboardEvent};
+use crate::subscription::SubscriptionFilter;
+
+// 每个 watchClipboard 迭代器的事件队列，监听线程把事件推入全部队列
+static WATCHERS: Mutex<Vec<Arc<WatchQueue>>> = Mutex::new(vec![]);
+
+// 队列默认容量
+const DEFAULT_WATCH_CAPACITY: usize = 100;
+
+type Resolver = Box<dyn FnOnce(Env) -> napi::Result<ClipboardWatchResult> + Send>;
+
+// watchClipboard 的可选配置
+#[napi(object)]
+#[derive(Default)]
+pub struct WatchOptions {
+    // 未被取走的事件最多保留多少个，超出时丢弃最旧的事件，默认 100
+    pub capacity: Option<u32>,
+    // 只接收匹配的事件，规则与 subscribe 相同
+    pub filter: Option<SubscriptionFilter>,
+}
+
+// 迭代结果，结构与 JS 的 IteratorResult 相同
+#[napi(object)]
+pub struct ClipboardWatchResult {
+    pub value: Option<ClipboardEvent>,
+    pub done: bool,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<aware::ClipboardEvent>,
+    // 还没有拿到事件的 next() 调用
+    waiters: VecDeque<JsDeferred<ClipboardWatchResult, Resolver>>,
+    closed: bool,
+    // 监听运行中失败的错误，取完已入队的事件后由 next() reject 一次
+    error: Option<aware::MonitorError>,
+    dropped: u64,
+}
+
+struct WatchQueue {
+    capacity: usize,
+    filter: aware::EventFilter,
+    state: Mutex<QueueState>,
+}
+
+impl WatchQueue {
+    // 队列已满、丢弃了最旧的事件时返回 false
+    fn push(&self, event: &aware::ClipboardEvent) -> bool {
+        let Some(event) = self.filter.apply(event) else {
+            return true;
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return true;
+        }
+        if let Some(waiter) = state.waiters.pop_front() {
+            resolve(waiter, Some(event));
+            return true;
+        }
+        let full = state.events.len() >= self.capacity;
+        if full {
+            state.events.pop_front();
+            state.dropped += 1;
+        }
+        state.events.push_back(event);
+        !full
+    }
+
+    // 结束迭代，等待中的 next() 全部返回 done
+    // discard 为 true 时丢弃未取走的事件 (break 退出循环)，否则仍可取完剩余事件
+    fn close(&self, discard: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        if discard {
+            state.events.clear();
+            state.error = None;
+        }
+        for waiter in state.waiters.drain(..) {
+            resolve(waiter, None);
+        }
+    }
+
+    // 监听失败：等待中的 next() 全部 reject，没有等待者时留给下一次 next()
+    fn fail(&self, error: &aware::MonitorError) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        if state.waiters.is_empty() {
+            state.error = Some(error.clone());
+        }
+        for waiter in state.waiters.drain(..) {
+            reject(waiter, error.clone());
+        }
+    }
+}
+
+fn reject(waiter: JsDeferred<ClipboardWatchResult, Resolver>, error: aware::MonitorError) {
+    waiter.resolve(Box::new(move |env| Err(monitor_error_value(&env, error))));
+}
+
+fn resolve(waiter: JsDeferred<ClipboardWatchResult, Resolver>, event: Option<aware::ClipboardEvent>) {
+    waiter.resolve(Box::new(move |_| {
+        Ok(ClipboardWatchResult { done: event.is_none(), value: event.map(Into::into) })
+    }));
+}
+
+// 剪贴板事件的异步迭代器，可直接用于 for await，退出循环时自动取消订阅
+#[napi]
+pub struct ClipboardWatcher {
+    queue: Arc<WatchQueue>,
+}
+
+#[napi]
+impl ClipboardWatcher {
+    // 取下一个事件，没有事件时等待；迭代结束后返回 done
+    #[napi(ts_return_type = "Promise<IteratorResult<ClipboardEvent, undefined>>")]
+    pub fn next<'env>(&self, env: &'env Env) -> napi::Result<Object<'env>> {
+        let (deferred, promise) = env.create_deferred::<ClipboardWatchResult, Resolver>()?;
+        let mut state = self.queue.state.lock().unwrap();
+        match state.events.pop_front() {
+            Some(event) => resolve(deferred, Some(event)),
+            None if state.closed => match state.error.take() {
+                Some(error) => reject(deferred, error),
+                None => resolve(deferred, None),
+            },
+            None => state.waiters.push_back(deferred),
+        }
+        Ok(promise)
+    }
+
+    // for await 中 break / return / throw 时由 JS 调用，取消订阅并结束迭代
+    #[napi(js_name = "return", ts_return_type = "Promise<IteratorResult<ClipboardEvent, undefined>>")]
+    pub fn finish<'env>(&self, env: &'env Env) -> napi::Result<Object<'env>> {
+        self.close();
+        let (deferred, promise) = env.create_deferred::<ClipboardWatchResult, Resolver>()?;
+        resolve(deferred, None);
+        Ok(promise)
+    }
+
+    // 取消订阅，丢弃未取走的事件
+    #[napi]
+    pub fn close(&self) {
+        WATCHERS.lock().unwrap().retain(|queue| !Arc::ptr_eq(queue, &self.queue));
+        self.queue.close(true);
+    }
+
+    // 队列满时丢弃的事件数量
+    #[napi(getter)]
+    pub fn dropped(&self) -> i64 {
+        self.queue.state.lock().unwrap().dropped as i64
+    }
+}
+
+// 迭代器被回收时同样取消订阅
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// 订阅剪贴板事件，返回异步迭代器：for await (const event of watchClipboard()) {...}
+// 需要先调用 clipboardInitialize 启动监听；clipboardShutdown 后迭代器取完剩余事件即结束
+#[napi(ts_return_type = "ClipboardWatcher & AsyncIterableIterator<ClipboardEvent>")]
+pub fn watch_clipboard<'env>(env: &'env Env, options: Option<WatchOptions>) -> napi::Result<Object<'env>> {
+    let options = options.unwrap_or_default();
+    let capacity = options.capacity.map_or(DEFAULT_WATCH_CAPACITY, |capacity| capacity.max(1) as usize);
+    let queue = Arc::new(WatchQueue {
+        capacity,
+        filter: options.filter.unwrap_or_default().into(),
+        state: Mutex::new(QueueState::default()),
+    });
+    WATCHERS.lock().unwrap().push(queue.clone());
+
+    let watcher = ClipboardWatcher { queue }.into_instance(env)?;
+    let mut object = watcher.as_object(env);
+    // [Symbol.asyncIterator]() 返回迭代器自身
+    let symbol: Unknown = env
+        .get_global()?
+        .get_named_property::<Function<(), Unknown>>("Symbol")?
+        .get_named_property("asyncIterator")?;
+    let iterator: Function<(), Unknown> = env.create_function("asyncIterator", return_this)?;
+    object.set_property(symbol, iterator)?;
+    Ok(object)
+}
+
+unsafe extern "C" fn return_this(env: sys::napi_env, info: sys::napi_callback_info) -> sys::napi_value {
+    let mut this = ptr::null_mut();
+    sys::napi_get_cb_info(env, info, ptr::null_mut(), ptr::null_mut(), &mut this, ptr::null_mut());
+    this
+}
+
+// 把事件推给全部迭代器，有迭代器丢弃了事件时返回 false
+pub fn dispatch(event: &aware::ClipboardEvent) -> bool {
+    let mut delivered = true;
+    for queue in WATCHERS.lock().unwrap().iter() {
+        delivered &= queue.push(event);
+    }
+    delivered
+}
+
+// 结束全部迭代器，已入队的事件仍可取完
+pub fn close_watchers() {
+    for queue in WATCHERS.lock().unwrap().drain(..) {
+        queue.close(false);
+    }
+}
+
+
+// 监听运行中失败，全部迭代器以该错误结束
+pub fn fail_watchers(error: &aware::MonitorError) {
+    for queue in WATCHERS.lock().unwrap().drain(..) {
+        queue.fail(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global::report;
+
+    fn cleared() -> aware::ClipboardEvent {
+        aware::ClipboardEvent {
+            seq: 1,
+            timestamp: 0,
+            pid: 0,
+            pname: String::new(),
+            selection: aware::Selection::CLIPBOARD,
+            formats: vec![],
+            report: aware::ClipboardReport::Cleared,
+        }
+    }
+
+    #[test]
+    fn full_queues_count_each_event_once() {
+        let queues: Vec<Arc<WatchQueue>> = (0..2)
+            .map(|_| {
+                Arc::new(WatchQueue { capacity: 1, filter: aware::EventFilter::default(), state: Mutex::new(QueueState::default()) })
+            })
+            .collect();
+        WATCHERS.lock().unwrap().extend(queues.iter().cloned());
+
+        let before = aware::stats::monitor_stats().events_dropped;
+        for _ in 0..3 {
+            report(cleared());
+        }
+        WATCHERS.lock().unwrap().retain(|queue| !queues.iter().any(|own| Arc::ptr_eq(queue, own)));
+
+        // 两个迭代器各丢弃了两个事件，但每个事件只计一次
+        for queue in &queues {
+            let state = queue.state.lock().unwrap();
+            assert_eq!((state.events.len(), state.dropped), (1, 2));
+        }
+        assert_eq!(aware::stats::monitor_stats().events_dropped - before, 2);
+    }
+}
@@ -1,16 +1,26 @@
-const { clipboardInitialize } = require('./index.js')
+const { clipboardInitialize, clipboardOnError, clipboardOnEvent } = require('./index.js')
+
+clipboardOnEvent((err, event) => {
+    console.log("事件上报:", event.seq, event.payload.kind, event.pname, event.formats);
+})
+
+clipboardOnError((err, info) => {
+    console.log("监听出错:", info.message, info.code);
+})
 
 clipboardInitialize(
     [
-        ".cpp", ".h", ".hpp", ".c", ".cs", ".py", ".java", ".js", ".ts", 
+        { name: "IMAGE", extensions: [".jpg", ".jpeg", ".png", ".bmp", ".gif", ".ico", ".tiff", ".webp"], mimeTypes: ["image/*"], priority: 2 },
+        { name: "EXCEL", extensions: [".xls", ".xlsx", ".csv", ".xlsm"], priority: 1 },
+        { name: "DOCUMENT", extensions: [".pdf", ".doc", ".docx", ".ppt", ".pptx"], mimeTypes: ["application/pdf"], priority: 1 },
+        {
+            name: "CODE",
+            extensions: [
+                ".cpp", ".h", ".hpp", ".c", ".cs", ".py", ".java", ".js", ".ts",
                 ".html", ".css", ".json", ".xml", ".sql", ".go", ".rs",
-    ], 
-    [
-        ".jpg", ".jpeg", ".png", ".bmp", ".gif", ".ico", ".tiff", ".webp",
-    ], 
-    [
-        ".xls", ".xlsx", ".csv", ".xlsm",
-    ], 
+            ],
+        },
+    ],
     (err, info) => {
         console.log("文件上报:", info);
     }, 
@@ -22,6 +32,10 @@ clipboardInitialize(
         console.log("日志上报:", info);
         
     },
-)
+).then(() => {
+    console.log("监听已启动");
+}).catch((e) => {
+    console.log("启动失败:", e.message, e.errno);
+})
 
 setInterval(() => {}, 1000); // 防止进程退出
\ No newline at end of file